@@ -16,6 +16,364 @@ pub struct CliArguments {
     /// The path to the root typefile.
     pub typefile: PathBuf,
 
-    #[clap(name = "targets", about = "A list of targets for the workflow.", default_value = "", index = 1)]
+    #[clap(
+        long,
+        about = "The directory in which typemake stores its state, such as logs.",
+        default_value = ".typemake"
+    )]
+    /// The directory in which typemake stores its state.
+    pub state_directory: PathBuf,
+
+    #[clap(
+        long,
+        short = 'v',
+        about = "Log more details to the terminal. Given once, debug messages are shown, and given twice, trace messages as well.",
+        parse(from_occurrences),
+        conflicts_with = "quiet"
+    )]
+    /// The number of times the verbosity of the terminal log was increased.
+    pub verbose: u64,
+
+    #[clap(
+        long,
+        short = 'q',
+        name = "quiet",
+        about = "Log less to the terminal. Given once, only warnings and errors are shown, given twice only errors, and given three times nothing.",
+        parse(from_occurrences)
+    )]
+    /// The number of times the verbosity of the terminal log was decreased.
+    pub quiet: u64,
+
+    #[clap(
+        long,
+        about = "Write a full trace log to the given file, independently of the verbosity of the terminal log."
+    )]
+    /// The file to write a full trace log to.
+    pub log_file: Option<PathBuf>,
+
+    #[clap(
+        long,
+        name = "configfile",
+        about = "A configuration file in YAML, TOML or JSON format, whose values are available to the typefile in the dict `config`. If given multiple times, later files override values of earlier ones.",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    /// The configuration files, in the order in which they are merged.
+    pub configfile: Vec<PathBuf>,
+
+    #[clap(
+        long,
+        name = "config",
+        about = "Set the configuration value with the given key, overriding the configuration files. Nested keys are separated by dots, and values are parsed as YAML.",
+        value_name = "key=value",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    /// Overrides of configuration values of the form `key=value`.
+    pub config: Vec<String>,
+
+    #[clap(
+        long,
+        name = "profile",
+        about = "Use the defaults of the given profile from typemake.toml in the working directory or the user configuration directory. If not given, the profile named default is used if it exists."
+    )]
+    /// The name of the profile providing defaults for the other arguments.
+    pub profile: Option<String>,
+
+    #[clap(
+        long,
+        short = 'j',
+        about = "The maximum number of tool instances executed in parallel. Defaults to 1."
+    )]
+    /// The maximum number of tool instances executed in parallel.
+    pub jobs: Option<usize>,
+
+    #[clap(
+        long,
+        about = "The time in seconds to wait for outputs of tool instances to appear, to account for filesystem latency. Defaults to 0."
+    )]
+    /// The time in seconds to wait for outputs of tool instances to appear.
+    pub latency_wait: Option<f64>,
+
+    #[clap(
+        long,
+        name = "executor",
+        about = "The executor running the tool instances. Defaults to local. The slurm executor submits them as SLURM jobs, and the cluster executor submits them to other batch systems using --submit-command, --status-command and --cancel-command.",
+        possible_values = &["local", "slurm", "cluster"]
+    )]
+    /// The executor running the tool instances.
+    pub executor: Option<String>,
+
+    #[clap(
+        long,
+        name = "submit-command",
+        about = "The command submitting a job script to the batch system, e.g. 'sbatch --parsable -c {threads} --mem {memory}'. It is given the path of the job script and must print the job id. The placeholders {name}, {attempt}, {threads}, {memory} and {timeout} are replaced by the properties of the tool instance."
+    )]
+    /// The command submitting a job script to the batch system, used by the cluster executor.
+    pub submit_command: Option<String>,
+
+    #[clap(
+        long,
+        name = "status-command",
+        about = "The command querying the status of a job of the batch system. It is given the job id and must print success or failed once the job terminated. If not given, the job is considered terminated once its script finished on the shared filesystem."
+    )]
+    /// The command querying the status of a job, used by the cluster executor.
+    pub status_command: Option<String>,
+
+    #[clap(
+        long,
+        name = "cancel-command",
+        about = "The command cancelling a job of the batch system, e.g. scancel. It is given the job id and used when typemake is interrupted."
+    )]
+    /// The command cancelling a job, used by the cluster executor.
+    pub cancel_command: Option<String>,
+
+    #[clap(
+        long,
+        name = "sbatch-arguments",
+        about = "Additional arguments passed to sbatch by the slurm executor, e.g. '--partition=short --account=lab'.",
+        allow_hyphen_values = true
+    )]
+    /// Additional arguments passed to `sbatch`, used by the slurm executor.
+    pub sbatch_arguments: Option<String>,
+
+    #[clap(
+        long,
+        name = "container-runtime",
+        about = "The runtime executing tool instances with a container image. Defaults to docker.",
+        possible_values = &["docker", "podman", "apptainer"]
+    )]
+    /// The runtime executing tool instances with a container image.
+    pub container_runtime: Option<String>,
+
+    #[clap(
+        long,
+        name = "resources",
+        about = "Request the given amount of a resource for all tool instances that do not declare it themselves. Supported resources are threads, memory in megabytes and timeout in seconds.",
+        value_name = "resource=amount",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    /// The resources requested for tool instances that do not declare them, of the form `resource=amount`.
+    pub resources: Vec<String>,
+
+    #[clap(
+        long,
+        short = 'k',
+        about = "Continue executing all tool instances that do not depend on a failed tool instance, and summarise the failures at the end."
+    )]
+    /// If set, a failing tool instance does not abort the execution of independent tool instances.
+    pub keep_going: bool,
+
+    #[clap(
+        long,
+        name = "no-keep-going",
+        about = "Abort the execution on the first failing tool instance, even if the profile enables --keep-going.",
+        conflicts_with = "keep-going"
+    )]
+    /// If set, a failing tool instance aborts the execution, regardless of the profile.
+    pub no_keep_going: bool,
+
+    #[clap(
+        long,
+        about = "Execute the tool instances producing the targets even if they are up to date, or all tool instances if no targets are given. Also overwrite protected outputs."
+    )]
+    /// If set, the tool instances producing the targets are executed regardless of their outputs, and protected outputs may be overwritten.
+    pub force: bool,
+
+    #[clap(
+        long,
+        name = "forcerun",
+        about = "Execute the instances of the given tool and all instances depending on them, even if they are up to date.",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    /// The tools whose instances are executed regardless of their outputs.
+    pub forcerun: Vec<String>,
+
+    #[clap(
+        long,
+        name = "until",
+        about = "Execute only the instances of the given tool and the instances they depend on.",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    /// The tools after which execution stops.
+    pub until: Vec<String>,
+
+    #[clap(
+        long,
+        name = "progress",
+        about = "Show a live display of the progress and the running tool instances. By default, it is shown only if stderr is a terminal.",
+        possible_values = &["auto", "always", "never"],
+        default_value = "auto"
+    )]
+    /// When to show the live progress display.
+    pub progress: String,
+
+    #[clap(
+        long,
+        name = "events",
+        about = "Write machine-readable events about the execution in the given format to the destination given by --events-to.",
+        possible_values = &["json"],
+        requires = "events-to"
+    )]
+    /// The format of the execution events. Only newline-delimited JSON is supported.
+    pub events: Option<String>,
+
+    #[clap(
+        long,
+        name = "events-to",
+        about = "The file to append the events to, or the unix socket to send them to.",
+        requires = "events"
+    )]
+    /// The destination of the execution events.
+    pub events_to: Option<PathBuf>,
+
+    #[clap(
+        name = "targets",
+        about = "A list of targets for the workflow.",
+        default_value = "",
+        index = 1
+    )]
+    /// The artifacts to produce. If empty, all tools are executed.
+    pub targets: Vec<String>,
+
+    #[clap(subcommand)]
+    /// A command other than executing the workflow.
+    pub command: Option<CliCommand>,
+}
+
+/// The commands of typemake other than executing the workflow.
+#[derive(Clap)]
+pub enum CliCommand {
+    #[clap(about = "Show the log of a tool instance.")]
+    /// Show the log of a tool instance.
+    Logs(LogsArguments),
+
+    #[clap(about = "Show statistics about the resources used by executed tool instances.")]
+    /// Show statistics about the resources used by executed tool instances.
+    Stats(StatsArguments),
+
+    #[clap(about = "Show which tool instances are done and which need to be executed.")]
+    /// Show which tool instances are done and which need to be executed.
+    Status(StatusArguments),
+
+    #[clap(about = "Remove outputs of tool instances, as well as their logs and metadata.")]
+    /// Remove outputs of tool instances, as well as their logs and metadata.
+    Clean(CleanArguments),
+
+    #[clap(about = "Remove the locks of typemake processes that are not running anymore.")]
+    /// Remove the locks of typemake processes that are not running anymore.
+    Unlock(UnlockArguments),
+}
+
+/// The command line arguments of the `logs` command.
+#[derive(Clap)]
+pub struct LogsArguments {
+    #[clap(
+        name = "instance",
+        about = "The tool instance whose log to show.",
+        index = 1
+    )]
+    /// The name of the tool instance whose log to show.
+    pub instance: String,
+
+    #[clap(
+        long,
+        short = 'f',
+        about = "Keep waiting for new lines appended to the log."
+    )]
+    /// If set, keep waiting for new lines appended to the log.
+    pub follow: bool,
+}
+
+/// The command line arguments of the `stats` command.
+#[derive(Clap)]
+pub struct StatsArguments {
+    #[clap(
+        long,
+        about = "The number of slowest executions to show.",
+        default_value = "10"
+    )]
+    /// The number of slowest executions to show.
+    pub slowest: usize,
+}
+
+/// The command line arguments of the `status` command.
+#[derive(Clap)]
+pub struct StatusArguments {
+    #[clap(
+        name = "targets",
+        about = "The targets whose status to show. If none are given, the status of all tools is shown.",
+        index = 1
+    )]
+    /// The artifacts whose producing tool instances to show. If empty, all tool instances are shown.
+    pub targets: Vec<String>,
+}
+
+/// The command line arguments of the `clean` command.
+#[derive(Clap)]
+pub struct CleanArguments {
+    #[clap(
+        name = "targets",
+        about = "The artifacts to remove. They need to be produced by a tool.",
+        index = 1
+    )]
+    /// The artifacts to remove.
     pub targets: Vec<String>,
+
+    #[clap(
+        long = "tool",
+        name = "tool",
+        about = "Remove all outputs of the given tool.",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    /// The tools whose outputs to remove.
+    pub tools: Vec<String>,
+
+    #[clap(long, about = "Remove all temporary outputs.")]
+    /// If set, remove all temporary outputs.
+    pub temp: bool,
+
+    #[clap(
+        long,
+        about = "Remove artifacts that were produced by tools that do not produce them anymore, as well as the logs and metadata of tools that do not exist anymore."
+    )]
+    /// If set, remove artifacts not produced by any tool anymore, and the state of tools that do not exist anymore.
+    pub orphaned: bool,
+
+    #[clap(long, about = "Also remove the logs of the selected tool instances.")]
+    /// If set, also remove the logs of the selected tool instances.
+    pub logs: bool,
+
+    #[clap(
+        long,
+        about = "Also remove the metadata of the selected tool instances, such as their benchmarks."
+    )]
+    /// If set, also remove the metadata of the selected tool instances.
+    pub metadata: bool,
+
+    #[clap(long, about = "Also remove protected outputs.")]
+    /// If set, protected outputs are removed as well.
+    pub force: bool,
+
+    #[clap(
+        long,
+        about = "Remove the listed files. Without this, the files that would be removed are only listed."
+    )]
+    /// If set, the selected files are removed instead of only being listed.
+    pub yes: bool,
+}
+
+/// The command line arguments of the `unlock` command.
+#[derive(Clap)]
+pub struct UnlockArguments {
+    #[clap(
+        long,
+        about = "Remove all locks, including those of processes that may still be running or that run on other hosts."
+    )]
+    /// If set, remove all locks instead of only the stale ones.
+    pub force: bool,
 }
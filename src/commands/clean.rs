@@ -0,0 +1,182 @@
+//! The `clean` command, removing outputs of tool instances as well as their logs and metadata.
+
+use crate::cli::CleanArguments;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{remove_dir_all, remove_file, symlink_metadata};
+use std::path::{Path, PathBuf};
+use typemake::error::{TypemakeError, TypemakeResult};
+use typemake::interpreter::Interpreter;
+use typemake::scheduler::Scheduler;
+use typemake::state::lock::WorkflowLock;
+use typemake::state::StateDirectory;
+
+/// Lists the files selected by the given arguments, and removes them if requested.
+/// The selection is based on the workflow graph built by the given scheduler and on the metadata store.
+pub fn clean<InterpreterType: Interpreter>(
+    scheduler: &Scheduler<'_, InterpreterType>,
+    state_directory: &StateDirectory,
+    clean_arguments: &CleanArguments,
+) -> TypemakeResult<()> {
+    let graph = scheduler.graph();
+    let metadata_store = state_directory.metadata_store();
+    // Maps the selected artifacts to the reason for their selection.
+    let mut artifacts = BTreeMap::new();
+    // The instances whose logs and metadata are removed if requested.
+    let mut instances = BTreeSet::new();
+    // The instances that do not exist anymore, whose logs and metadata are always removed.
+    let mut orphaned_instances = BTreeSet::new();
+
+    for target in &clean_arguments.targets {
+        let producer = graph.producer(target).ok_or_else(|| {
+            TypemakeError::GeneralError(format!("No tool instance produces target {:?}", target))
+        })?;
+        let producer_name = &graph.tool_instance(producer).name;
+        artifacts.insert(
+            target.clone(),
+            format!("output of tool instance {:?}", producer_name),
+        );
+        instances.insert(producer_name.clone());
+    }
+
+    for tool in &clean_arguments.tools {
+        let node = graph.instance_node(tool).ok_or_else(|| {
+            TypemakeError::GeneralError(format!("No tool instance named {:?}", tool))
+        })?;
+        for output in &graph.tool_instance(node).outputs {
+            artifacts.insert(
+                output.clone(),
+                format!("output of tool instance {:?}", tool),
+            );
+        }
+        instances.insert(tool.clone());
+    }
+
+    if clean_arguments.temp {
+        for node in graph.nodes() {
+            let tool_instance = graph.tool_instance(node);
+            for output in &tool_instance.outputs {
+                if tool_instance.output_flags(output).temp {
+                    artifacts.insert(
+                        output.clone(),
+                        format!("temporary output of tool instance {:?}", tool_instance.name),
+                    );
+                }
+            }
+        }
+    }
+
+    if clean_arguments.orphaned {
+        // Tools that are not instantiated yet, e.g. because they depend on a checkpoint, still exist.
+        let uninstantiated_tools: BTreeSet<&str> = scheduler
+            .uninstantiated_tools()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        for instance_name in metadata_store.instance_names()? {
+            if uninstantiated_tools.contains(instance_name.as_str()) {
+                continue;
+            }
+            for output in metadata_store.load(&instance_name)?.outputs {
+                if graph.producer(&output).is_none() {
+                    artifacts.insert(
+                        output,
+                        format!("orphaned output of tool instance {:?}", instance_name),
+                    );
+                }
+            }
+            if graph.instance_node(&instance_name).is_none() {
+                orphaned_instances.insert(instance_name);
+            }
+        }
+    }
+
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for (artifact, reason) in artifacts {
+        if symlink_metadata(&artifact).is_err() {
+            continue;
+        }
+        let is_protected = graph.producer(&artifact).is_some_and(|producer| {
+            graph
+                .tool_instance(producer)
+                .output_flags(&artifact)
+                .protected
+        });
+        if is_protected && !clean_arguments.force {
+            println!(
+                "Skipping protected {} {:?}, use --force to remove it",
+                reason, artifact
+            );
+            continue;
+        }
+        files.push((artifact.into(), reason));
+    }
+
+    let mut metadata_instances = Vec::new();
+    for instance_name in instances.iter().chain(&orphaned_instances) {
+        let is_orphaned = orphaned_instances.contains(instance_name);
+        if clean_arguments.logs || is_orphaned {
+            let log_path = state_directory.log_path(instance_name);
+            if symlink_metadata(&log_path).is_ok() {
+                files.push((
+                    log_path,
+                    format!("log of tool instance {:?}", instance_name),
+                ));
+            }
+        }
+        if clean_arguments.metadata || is_orphaned {
+            metadata_instances.push(instance_name.clone());
+        }
+    }
+
+    if files.is_empty() && metadata_instances.is_empty() {
+        println!("Nothing to clean");
+        return Ok(());
+    }
+
+    println!(
+        "{}:",
+        if clean_arguments.yes {
+            "Removing"
+        } else {
+            "Would remove"
+        }
+    );
+    for (path, reason) in &files {
+        println!("  {:?} ({})", path, reason);
+    }
+    for instance_name in &metadata_instances {
+        println!("  metadata of tool instance {:?}", instance_name);
+    }
+
+    if !clean_arguments.yes {
+        println!("Run again with --yes to remove the listed files");
+        return Ok(());
+    }
+
+    // Make sure that no running typemake process produces the artifacts while they are removed.
+    let _lock = WorkflowLock::acquire(
+        state_directory.lock_directory(),
+        files
+            .iter()
+            .map(|(path, _)| path.to_string_lossy().into_owned())
+            .collect(),
+    )?;
+    for (path, _) in &files {
+        remove(path)?;
+    }
+    for instance_name in &metadata_instances {
+        metadata_store.remove(instance_name)?;
+    }
+    Ok(())
+}
+
+/// Removes the given file or directory.
+/// Symlinks are removed without touching their targets.
+fn remove(path: &Path) -> TypemakeResult<()> {
+    if symlink_metadata(path)?.is_dir() {
+        remove_dir_all(path)?;
+    } else {
+        remove_file(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! The `logs` command, showing the captured output of a tool instance.
+
+use crate::cli::LogsArguments;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::time::Duration;
+use typemake::error::{TypemakeError, TypemakeResult};
+use typemake::state::StateDirectory;
+
+/// The interval in which a followed log is checked for new content.
+const LOG_FOLLOW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Writes the log of a tool instance to stdout.
+/// If requested, keeps waiting for new content appended to the log.
+pub fn show_log(
+    state_directory: &StateDirectory,
+    logs_arguments: &LogsArguments,
+) -> TypemakeResult<()> {
+    let log_path = state_directory.log_path(&logs_arguments.instance);
+    let mut log = File::open(&log_path).map_err(|error| {
+        TypemakeError::GeneralError(format!(
+            "Could not open log of tool instance {:?} at {:?}: {}",
+            logs_arguments.instance, log_path, error
+        ))
+    })?;
+
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    let mut buffer = Vec::new();
+    loop {
+        log.read_to_end(&mut buffer)?;
+        stdout.write_all(&buffer)?;
+        stdout.flush()?;
+        buffer.clear();
+
+        if !logs_arguments.follow {
+            return Ok(());
+        }
+        std::thread::sleep(LOG_FOLLOW_INTERVAL);
+    }
+}
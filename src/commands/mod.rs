@@ -0,0 +1,198 @@
+//! The commands of typemake, dispatched from the cli-arguments.
+
+use crate::cli::{CleanArguments, CliArguments, CliCommand, StatusArguments};
+use crate::commands::clean::clean;
+use crate::commands::logs::show_log;
+use crate::commands::stats::show_stats;
+use crate::commands::status::show_status;
+use crate::commands::unlock::unlock;
+use log::info;
+use std::time::Duration;
+use typemake::error::{TypemakeError, TypemakeResult};
+use typemake::events::EventSink;
+use typemake::executor::cluster::ClusterExecutor;
+use typemake::executor::slurm::SlurmExecutor;
+use typemake::progress::stderr_is_terminal;
+use typemake::scheduler::{Scheduler, SchedulerOptions};
+use typemake::state::StateDirectory;
+use typemake::workflow::Resources;
+use typemake::{load_workflow, run_workflow, Executor, WorkflowOptions};
+
+pub mod clean;
+pub mod logs;
+pub mod stats;
+pub mod status;
+pub mod unlock;
+
+/// Runs typemake with the given cli-arguments.
+/// This is the entrypoint into typemakes business logic.
+pub fn run_typemake_from_cli(cli_arguments: &CliArguments) -> TypemakeResult<()> {
+    let state_directory = StateDirectory::new(&cli_arguments.state_directory);
+    match &cli_arguments.command {
+        Some(CliCommand::Logs(logs_arguments)) => show_log(&state_directory, logs_arguments),
+        Some(CliCommand::Stats(stats_arguments)) => show_stats(&state_directory, stats_arguments),
+        Some(CliCommand::Status(status_arguments)) => {
+            status_workflow(cli_arguments, state_directory, status_arguments)
+        }
+        Some(CliCommand::Clean(clean_arguments)) => {
+            clean_workflow(cli_arguments, state_directory, clean_arguments)
+        }
+        Some(CliCommand::Unlock(unlock_arguments)) => unlock(&state_directory, unlock_arguments),
+        None => run_workflow(workflow_options(cli_arguments, &state_directory)?),
+    }
+}
+
+/// Translates the cli-arguments into the options for executing the workflow.
+fn workflow_options(
+    cli_arguments: &CliArguments,
+    state_directory: &StateDirectory,
+) -> TypemakeResult<WorkflowOptions> {
+    let executor: Option<Box<dyn Executor>> =
+        match cli_arguments.executor.as_deref().unwrap_or("local") {
+            "local" => None,
+            "slurm" => Some(Box::new(SlurmExecutor::new(
+                state_directory.slurm_directory(),
+                cli_arguments.sbatch_arguments.clone(),
+            ))),
+            "cluster" => Some(Box::new(ClusterExecutor::new(
+                cli_arguments.submit_command.clone().ok_or_else(|| {
+                    TypemakeError::GeneralError(
+                        "The cluster executor requires --submit-command".to_owned(),
+                    )
+                })?,
+                cli_arguments.status_command.clone(),
+                cli_arguments.cancel_command.clone(),
+            ))),
+            executor => {
+                return Err(TypemakeError::GeneralError(format!(
+                    "Unknown executor {:?}",
+                    executor
+                )))
+            }
+        };
+    let events = if let Some(destination) = &cli_arguments.events_to {
+        info!("Writing events to {:?}", destination);
+        Some(EventSink::open(destination)?)
+    } else {
+        None
+    };
+
+    Ok(WorkflowOptions {
+        typefile: cli_arguments.typefile.clone(),
+        state_directory: cli_arguments.state_directory.clone(),
+        targets: cli_arguments
+            .targets
+            .iter()
+            .filter(|target| !target.is_empty())
+            .cloned()
+            .collect(),
+        config_files: cli_arguments.configfile.clone(),
+        config_overrides: cli_arguments.config.clone(),
+        scheduler: SchedulerOptions {
+            jobs: cli_arguments.jobs.unwrap_or(1),
+            latency_wait: Duration::try_from_secs_f64(cli_arguments.latency_wait.unwrap_or(0.0))
+                .map_err(|_| {
+                    TypemakeError::GeneralError(format!(
+                        "Expected a finite non-negative number of seconds to wait for outputs, but got {}",
+                        cli_arguments.latency_wait.unwrap_or(0.0)
+                    ))
+                })?,
+            default_resources: parse_resources(&cli_arguments.resources)?,
+            keep_going: cli_arguments.keep_going,
+            progress: match cli_arguments.progress.as_str() {
+                "always" => true,
+                "never" => false,
+                _ => stderr_is_terminal(),
+            },
+            force: cli_arguments.force,
+            forcerun: cli_arguments.forcerun.clone(),
+            until: cli_arguments.until.clone(),
+            container_runtime: cli_arguments
+                .container_runtime
+                .as_deref()
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or_default(),
+        },
+        events,
+        executor,
+    })
+}
+
+/// Removes the files selected by the `clean` command.
+fn clean_workflow(
+    cli_arguments: &CliArguments,
+    state_directory: StateDirectory,
+    clean_arguments: &CleanArguments,
+) -> TypemakeResult<()> {
+    let (workflow, mut interpreter) = load_workflow(
+        &cli_arguments.typefile,
+        &cli_arguments.configfile,
+        &cli_arguments.config,
+    )?;
+
+    info!("Building workflow DAG");
+    let scheduler = Scheduler::new(
+        workflow.tools,
+        workflow.types,
+        &mut interpreter,
+        Vec::new(),
+        SchedulerOptions::default(),
+        state_directory.clone(),
+    )?;
+    clean(&scheduler, &state_directory, clean_arguments)
+}
+
+/// Shows the status of the tool instances required to produce the targets of the `status` command.
+fn status_workflow(
+    cli_arguments: &CliArguments,
+    state_directory: StateDirectory,
+    status_arguments: &StatusArguments,
+) -> TypemakeResult<()> {
+    let (workflow, mut interpreter) = load_workflow(
+        &cli_arguments.typefile,
+        &cli_arguments.configfile,
+        &cli_arguments.config,
+    )?;
+
+    info!("Building workflow DAG");
+    let scheduler = Scheduler::new(
+        workflow.tools,
+        workflow.types,
+        &mut interpreter,
+        status_arguments.targets.clone(),
+        SchedulerOptions::default(),
+        state_directory,
+    )?;
+    show_status(&scheduler)
+}
+
+/// Parses the given resources of the form `resource=amount`.
+/// Later values of the same resource override earlier ones.
+fn parse_resources(resources: &[String]) -> TypemakeResult<Resources> {
+    let mut result = Resources::default();
+    for resource in resources {
+        let invalid = || {
+            TypemakeError::GeneralError(format!(
+                "Expected a resource of the form threads=amount, memory=amount or timeout=amount, but got {:?}",
+                resource
+            ))
+        };
+        let (name, amount) = resource.split_once('=').ok_or_else(invalid)?;
+        match name {
+            "threads" => result.threads = Some(amount.parse().map_err(|_| invalid())?),
+            "memory" => result.memory = Some(amount.parse().map_err(|_| invalid())?),
+            "timeout" => {
+                result.timeout = Some(
+                    amount
+                        .parse()
+                        .ok()
+                        .filter(|timeout: &f64| timeout.is_finite() && *timeout >= 0.0)
+                        .ok_or_else(invalid)?,
+                )
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(result)
+}
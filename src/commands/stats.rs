@@ -0,0 +1,118 @@
+//! The `stats` command, showing the resources used by executed tool instances.
+
+use crate::cli::StatsArguments;
+use typemake::error::TypemakeResult;
+use typemake::state::metadata::Benchmark;
+use typemake::state::StateDirectory;
+
+/// Aggregated resource usage of all executions of a tool.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ToolStatistics {
+    /// The number of executions.
+    executions: usize,
+    /// The number of failed executions.
+    failures: usize,
+    /// The sum of the wall-clock times of all executions in seconds.
+    total_wall_time: f64,
+    /// The maximum wall-clock time of any execution in seconds.
+    max_wall_time: f64,
+    /// The sum of the CPU times (user and system) of all executions in seconds.
+    total_cpu_time: f64,
+    /// The maximum peak resident set size of any execution in bytes.
+    max_rss: u64,
+}
+
+impl ToolStatistics {
+    /// Adds a single execution to the statistics.
+    fn add(&mut self, benchmark: &Benchmark) {
+        self.executions += 1;
+        if !benchmark.success {
+            self.failures += 1;
+        }
+        self.total_wall_time += benchmark.wall_time;
+        self.max_wall_time = self.max_wall_time.max(benchmark.wall_time);
+        self.total_cpu_time += benchmark.user_time + benchmark.system_time;
+        self.max_rss = self.max_rss.max(benchmark.max_rss);
+    }
+}
+
+/// Prints per-tool aggregates of the recorded benchmarks as well as the slowest executions.
+pub fn show_stats(
+    state_directory: &StateDirectory,
+    stats_arguments: &StatsArguments,
+) -> TypemakeResult<()> {
+    let metadata_store = state_directory.metadata_store();
+    let mut executions = Vec::new();
+    println!(
+        "{:<30} {:>6} {:>6} {:>10} {:>10} {:>10} {:>10}",
+        "tool", "runs", "failed", "mean wall", "max wall", "mean cpu", "max rss"
+    );
+
+    // Each tool is instantiated at most once, so the instance name equals the tool name.
+    for instance_name in metadata_store.instance_names()? {
+        let metadata = metadata_store.load(&instance_name)?;
+        if metadata.benchmarks.is_empty() {
+            continue;
+        }
+
+        let mut statistics = ToolStatistics::default();
+        for benchmark in &metadata.benchmarks {
+            statistics.add(benchmark);
+        }
+        println!(
+            "{:<30} {:>6} {:>6} {:>10} {:>10} {:>10} {:>10}",
+            instance_name,
+            statistics.executions,
+            statistics.failures,
+            format_seconds(statistics.total_wall_time / statistics.executions as f64),
+            format_seconds(statistics.max_wall_time),
+            format_seconds(statistics.total_cpu_time / statistics.executions as f64),
+            format_bytes(statistics.max_rss),
+        );
+
+        executions.extend(
+            metadata
+                .benchmarks
+                .into_iter()
+                .map(|benchmark| (instance_name.clone(), benchmark)),
+        );
+    }
+
+    executions.sort_by(|(_, a), (_, b)| b.wall_time.total_cmp(&a.wall_time));
+    println!();
+    println!("Slowest executions:");
+    println!(
+        "{:<30} {:>10} {:>10} {:>10} {:>10} {:>7}",
+        "instance", "wall", "user", "system", "max rss", "success"
+    );
+    for (instance_name, benchmark) in executions.iter().take(stats_arguments.slowest) {
+        println!(
+            "{:<30} {:>10} {:>10} {:>10} {:>10} {:>7}",
+            instance_name,
+            format_seconds(benchmark.wall_time),
+            format_seconds(benchmark.user_time),
+            format_seconds(benchmark.system_time),
+            format_bytes(benchmark.max_rss),
+            benchmark.success,
+        );
+    }
+
+    Ok(())
+}
+
+/// Formats a duration given in seconds for display in a table.
+fn format_seconds(seconds: f64) -> String {
+    format!("{:.2}s", seconds)
+}
+
+/// Formats an amount of bytes in binary units for display in a table.
+fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < units.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, units[unit])
+}
@@ -0,0 +1,62 @@
+//! The `status` command, showing which tool instances are done and which need to be executed.
+
+use typemake::error::TypemakeResult;
+use typemake::interpreter::Interpreter;
+use typemake::scheduler::{InstanceStatus, Scheduler};
+
+/// Prints the status of each tool required to produce the targets of the scheduler, followed by a summary.
+/// Tools that could not be instantiated yet are counted as pending.
+pub fn show_status<InterpreterType: Interpreter>(
+    scheduler: &Scheduler<'_, InterpreterType>,
+) -> TypemakeResult<()> {
+    // Counts of done, stale, pending, failed and running instances.
+    let mut totals = [0usize; 5];
+    println!(
+        "{:<30} {:>5} {:>5} {:>7} {:>6} {:>7}  reason",
+        "tool", "done", "stale", "pending", "failed", "running"
+    );
+
+    let mut rows = Vec::new();
+    // Each tool is instantiated at most once, so the instance name equals the tool name.
+    for (tool_instance, status) in scheduler.instance_statuses()? {
+        let (column, reason) = match status {
+            InstanceStatus::Done => (0, String::new()),
+            InstanceStatus::Stale(reason) => (1, reason),
+            InstanceStatus::Pending => (2, String::new()),
+            InstanceStatus::Failed => (3, String::new()),
+            InstanceStatus::Running => (4, String::new()),
+        };
+        rows.push((tool_instance.name.clone(), column, reason));
+    }
+    for (tool, errors) in scheduler.uninstantiated_tools() {
+        rows.push((
+            tool.to_owned(),
+            2,
+            format!("not instantiated yet: {}", errors.join("; ")),
+        ));
+    }
+    rows.sort();
+
+    for (tool, column, reason) in rows {
+        let mut counts = [0usize; 5];
+        counts[column] += 1;
+        totals[column] += 1;
+        println!(
+            "{:<30} {:>5} {:>5} {:>7} {:>6} {:>7}  {}",
+            tool, counts[0], counts[1], counts[2], counts[3], counts[4], reason
+        );
+    }
+
+    let total: usize = totals.iter().sum();
+    let percentage = if total == 0 {
+        100.0
+    } else {
+        totals[0] as f64 * 100.0 / total as f64
+    };
+    println!();
+    println!(
+        "{} of {} tool instances done ({:.1}%), {} stale, {} pending, {} failed, {} running",
+        totals[0], total, percentage, totals[1], totals[2], totals[3], totals[4]
+    );
+    Ok(())
+}
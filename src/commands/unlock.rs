@@ -0,0 +1,25 @@
+//! The `unlock` command, removing stale workflow locks.
+
+use crate::cli::UnlockArguments;
+use log::info;
+use typemake::error::TypemakeResult;
+use typemake::state::lock::remove_locks;
+use typemake::state::StateDirectory;
+
+/// Removes the locks of typemake processes that are not running anymore, or all locks if forced.
+pub fn unlock(
+    state_directory: &StateDirectory,
+    unlock_arguments: &UnlockArguments,
+) -> TypemakeResult<()> {
+    let removed = remove_locks(&state_directory.lock_directory(), unlock_arguments.force)?;
+    for lock in &removed {
+        info!(
+            "Removed lock of pid {} on host {} covering {} artifacts",
+            lock.pid,
+            lock.host,
+            lock.artifacts.len()
+        );
+    }
+    info!("Removed {} locks", removed.len());
+    Ok(())
+}
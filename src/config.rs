@@ -0,0 +1,115 @@
+//! The configuration of a workflow, given by configuration files and overrides on the command line.
+//!
+//! The configuration is a mapping from keys to arbitrary values, which is exposed to the typefile as the python dict `config`.
+//! Configuration files are merged in the given order, such that later files override values of earlier ones,
+//! and overrides on the command line take precedence over all files.
+//! Nested mappings are merged recursively.
+
+use crate::error::{TypemakeError, TypemakeResult};
+use crate::interpreter::InterpreterValue;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// The configuration of a workflow.
+pub type Config = Map<String, Value>;
+
+/// Loads the given configuration files and applies the given overrides of the form `key=value` to them.
+/// Keys of overrides may refer to nested values by separating the keys of the nested mappings with dots, e.g. `dataset.name=small`.
+/// Values of overrides are parsed as YAML, such that e.g. `threads=4` results in an integer, and values that are not valid YAML are used as strings.
+pub fn load_config(
+    config_files: &[impl AsRef<Path>],
+    overrides: &[String],
+) -> TypemakeResult<Config> {
+    let mut config = Config::new();
+    for config_file in config_files {
+        merge(&mut config, read_config_file(config_file.as_ref())?);
+    }
+
+    for config_override in overrides {
+        let (key, value) = config_override.split_once('=').ok_or_else(|| {
+            TypemakeError::ConfigError(format!(
+                "expected an override of the form key=value, but got {:?}",
+                config_override
+            ))
+        })?;
+        let value = serde_yaml::from_str(value).unwrap_or_else(|_| Value::String(value.to_owned()));
+        let value = key.rsplit('.').fold(value, |value, key| {
+            let mut mapping = Config::new();
+            mapping.insert(key.to_owned(), value);
+            Value::Object(mapping)
+        });
+        if let Value::Object(mapping) = value {
+            merge(&mut config, mapping);
+        }
+    }
+    Ok(config)
+}
+
+/// Reads a configuration file in the format given by its extension, which is one of YAML, TOML and JSON.
+fn read_config_file(path: &Path) -> TypemakeResult<Config> {
+    let content = std::fs::read_to_string(path)?;
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let value: Value = match extension.as_deref() {
+        Some("yaml") | Some("yml") => {
+            serde_yaml::from_str(&content).map_err(|error| error.to_string())
+        }
+        Some("toml") => toml::from_str(&content).map_err(|error| error.to_string()),
+        Some("json") => serde_json::from_str(&content).map_err(|error| error.to_string()),
+        _ => Err(
+            "unknown format, expected one of the extensions .yaml, .yml, .toml and .json"
+                .to_owned(),
+        ),
+    }
+    .map_err(|error| TypemakeError::ConfigError(format!("{:?}: {}", path, error)))?;
+
+    match value {
+        Value::Object(config) => Ok(config),
+        Value::Null => Ok(Config::new()),
+        _ => Err(TypemakeError::ConfigError(format!(
+            "{:?}: expected a mapping at the top level",
+            path
+        ))),
+    }
+}
+
+/// Merges `other` into `config`, replacing values of `config` except for mappings, which are merged recursively.
+fn merge(config: &mut Config, other: Config) {
+    for (key, value) in other {
+        match (config.get_mut(&key), value) {
+            (Some(Value::Object(existing)), Value::Object(value)) => merge(existing, value),
+            (_, value) => {
+                config.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Converts the given configuration into a value that can be passed to the interpreter.
+pub fn config_value(config: &Config) -> InterpreterValue {
+    json_to_interpreter_value(&Value::Object(config.clone()))
+}
+
+/// Converts a JSON value into an interpreter value.
+/// Numbers are converted into integers if possible, and into floats otherwise.
+fn json_to_interpreter_value(value: &Value) -> InterpreterValue {
+    match value {
+        Value::Null => InterpreterValue::None,
+        Value::Bool(boolean) => InterpreterValue::Bool(*boolean),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => InterpreterValue::Int(integer),
+            None => InterpreterValue::Float(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(string) => InterpreterValue::String(string.clone()),
+        Value::Array(array) => {
+            InterpreterValue::List(array.iter().map(json_to_interpreter_value).collect())
+        }
+        Value::Object(object) => InterpreterValue::Dict(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), json_to_interpreter_value(value)))
+                .collect(),
+        ),
+    }
+}
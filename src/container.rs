@@ -0,0 +1,190 @@
+//! Execution of tool scripts in containers.
+//!
+//! The script of a tool instance with a `container` property is wrapped into a command running it in the given image with a container runtime.
+//! The staging directory of the instance is bind-mounted read-write and used as working directory inside the container,
+//! while its inputs are bind-mounted read-only at their paths on the host, such that the symlinks of the staging directory resolve inside the container.
+//! Outputs that are not staged are produced in their parent directories, which are bind-mounted read-write.
+//! The software environment of the instance, if any, is created on the host and bind-mounted read-only, such that it can be activated inside the container.
+//! Only conda environments are self-contained prefixes that work this way, so the parser rejects `venv` and `nix` environments of tools with a container.
+
+use crate::error::{TypemakeError, TypemakeResult};
+use crate::executor::job_script::{run_command, shell_quote};
+use crate::state::staging::StagingDirectory;
+use crate::workflow::ToolInstance;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The environment variables describing the attempt, which are passed into the container.
+const ATTEMPT_VARIABLES: [&str; 3] = ["TYPEMAKE_ATTEMPT", "TYPEMAKE_THREADS", "TYPEMAKE_MEMORY"];
+
+/// The command line tool running containers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContainerRuntime {
+    /// Docker, running the container as the current user.
+    #[default]
+    Docker,
+    /// Podman, which maps the current user into the container by itself if running rootless.
+    Podman,
+    /// Apptainer, formerly known as Singularity, which runs the container as the current user with the host environment.
+    Apptainer,
+}
+
+impl FromStr for ContainerRuntime {
+    type Err = TypemakeError;
+
+    fn from_str(runtime: &str) -> Result<Self, Self::Err> {
+        match runtime {
+            "docker" => Ok(Self::Docker),
+            "podman" => Ok(Self::Podman),
+            "apptainer" => Ok(Self::Apptainer),
+            runtime => Err(TypemakeError::GeneralError(format!(
+                "Unknown container runtime {:?}",
+                runtime
+            ))),
+        }
+    }
+}
+
+impl ContainerRuntime {
+    /// Returns the name of the executable of the runtime.
+    pub fn command(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+            Self::Apptainer => "apptainer",
+        }
+    }
+
+    /// Returns a script running the script of the given tool instance with bash inside a container of the given image,
+    /// using the given staging directory as working directory.
+    /// The given directory of the software environment of the instance, if any, is made available inside the container.
+    pub fn wrap_script(
+        &self,
+        tool_instance: &ToolInstance,
+        image: &str,
+        staging_directory: &StagingDirectory,
+        environment_directory: Option<&Path>,
+    ) -> TypemakeResult<String> {
+        let mut command = vec![self.command().to_owned()];
+        match self {
+            Self::Docker | Self::Podman => {
+                command.push("run".to_owned());
+                command.push("--rm".to_owned());
+                if *self == Self::Docker {
+                    command.push("--user".to_owned());
+                    command.push("\"$(id -u):$(id -g)\"".to_owned());
+                }
+                command.push("--workdir".to_owned());
+            }
+            Self::Apptainer => {
+                command.push("exec".to_owned());
+                command.push("--pwd".to_owned());
+            }
+        }
+
+        command.push(shell_quote(
+            std::env::current_dir()?.join(staging_directory.path()),
+        ));
+        for (path, writable) in
+            bind_mounts(tool_instance, staging_directory, environment_directory)?
+        {
+            command.push(
+                match self {
+                    Self::Docker | Self::Podman => "--volume",
+                    Self::Apptainer => "--bind",
+                }
+                .to_owned(),
+            );
+            command.push(shell_quote(format!(
+                "{}:{}{}",
+                path.display(),
+                path.display(),
+                if writable { "" } else { ":ro" }
+            )));
+        }
+        // Apptainer passes the environment of the host into the container by default.
+        if *self != Self::Apptainer {
+            for variable in ATTEMPT_VARIABLES {
+                command.push("--env".to_owned());
+                command.push(variable.to_owned());
+            }
+        }
+
+        command.push(shell_quote(image));
+        command.push("bash".to_owned());
+        command.push("-c".to_owned());
+        command.push(shell_quote(&tool_instance.script));
+        Ok(format!("exec {}\n", command.join(" ")))
+    }
+
+    /// Returns the digest identifying the contents of the given image, or `None` if the runtime cannot determine it.
+    /// Docker and podman report the id of the local image, while for apptainer, the digest of an image file is computed.
+    pub fn image_digest(&self, image: &str) -> TypemakeResult<Option<String>> {
+        match self {
+            Self::Docker | Self::Podman => {
+                let digest = run_command(&format!(
+                    "{} image inspect --format '{{{{.Id}}}}' {}",
+                    self.command(),
+                    shell_quote(image)
+                ))?;
+                Ok(Some(digest.trim().to_owned()).filter(|digest| !digest.is_empty()))
+            }
+            Self::Apptainer => {
+                if !Path::new(image).is_file() {
+                    return Ok(None);
+                }
+                let mut hasher = Sha256::new();
+                std::io::copy(&mut File::open(image)?, &mut hasher)?;
+                Ok(Some(format!("sha256:{:x}", hasher.finalize())))
+            }
+        }
+    }
+}
+
+/// Returns the absolute paths to bind-mount into the container of the given tool instance, mapped to true if they need to be writable.
+/// The parent directories of outputs that are not staged are created if necessary.
+fn bind_mounts(
+    tool_instance: &ToolInstance,
+    staging_directory: &StagingDirectory,
+    environment_directory: Option<&Path>,
+) -> TypemakeResult<BTreeMap<PathBuf, bool>> {
+    let working_directory = std::env::current_dir()?;
+    let mut mounts = BTreeMap::new();
+    mounts.insert(working_directory.join(staging_directory.path()), true);
+    for output in &tool_instance.outputs {
+        let path = staging_directory.output_path(output);
+        if path.starts_with(staging_directory.path()) {
+            continue;
+        }
+        if let Some(parent) = working_directory.join(path).parent() {
+            create_dir_all(parent)?;
+            mounts.insert(parent.canonicalize()?, true);
+        }
+    }
+    for input in &tool_instance.inputs {
+        mounts
+            .entry(normalize(&working_directory.join(input))?)
+            .or_insert(false);
+    }
+    // The environment directory is created by the job before the container starts, so it cannot be normalized here.
+    if let Some(environment_directory) = environment_directory {
+        mounts
+            .entry(environment_directory.to_owned())
+            .or_insert(false);
+    }
+    Ok(mounts)
+}
+
+/// Resolves `.` and `..` components and symlinks in the parent directories of the given absolute path,
+/// keeping its last component, which may be a symlink itself.
+fn normalize(path: &Path) -> TypemakeResult<PathBuf> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(file_name)) if parent.exists() => {
+            Ok(parent.canonicalize()?.join(file_name))
+        }
+        _ => Ok(path.to_owned()),
+    }
+}
@@ -0,0 +1,170 @@
+//! Software environments of tools, which typemake creates from a specification file and caches in the state directory.
+//!
+//! A tool declares its environment with one of the properties `conda`, `venv` or `nix`, giving the path of the specification:
+//! a conda environment file, a pip requirements file, or a nix expression evaluating to a derivation with a `bin` directory.
+//! Each environment is created once into a directory named after the hash of its kind and specification,
+//! such that tools with the same specification share it, and a changed specification results in a new environment.
+//! The environment is created and activated by commands inserted in front of the script of the tool instance,
+//! such that it is created by the first job using it, without blocking typemake while it is created.
+
+use crate::error::{TypemakeError, TypemakeResult};
+use crate::executor::job_script::shell_quote;
+use sha2::{Digest, Sha256};
+use std::fs::read;
+use std::path::{Path, PathBuf};
+
+/// The number of hexadecimal digits of the hash used in the name of an environment directory.
+const DIRECTORY_HASH_LENGTH: usize = 16;
+
+/// The kind of a software environment, i.e. the tool creating it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvironmentKind {
+    /// A conda environment, created with `conda env create` from an environment file.
+    Conda,
+    /// A python virtual environment, created with `python3 -m venv` and populated with `pip` from a requirements file.
+    Venv,
+    /// A nix environment, built with `nix-build` from a nix expression.
+    Nix,
+}
+
+impl EnvironmentKind {
+    /// Returns the name of the tool property declaring an environment of this kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Conda => "conda",
+            Self::Venv => "venv",
+            Self::Nix => "nix",
+        }
+    }
+
+    /// Returns the command creating an environment of this kind from the given specification in the given directory.
+    fn create_command(&self, spec: &Path, directory: &Path) -> String {
+        match self {
+            Self::Conda => format!(
+                "conda env create --prefix {} --file {}",
+                shell_quote(directory),
+                shell_quote(spec)
+            ),
+            Self::Venv => format!(
+                "python3 -m venv {} && {} install --requirement {}",
+                shell_quote(directory),
+                shell_quote(directory.join("bin").join("pip")),
+                shell_quote(spec)
+            ),
+            Self::Nix => format!(
+                "nix-build {} --out-link {}",
+                shell_quote(spec),
+                shell_quote(directory)
+            ),
+        }
+    }
+
+    /// Returns the shell commands activating the environment of this kind in the given directory.
+    fn activation(&self, directory: &Path) -> String {
+        match self {
+            Self::Conda => format!(
+                "eval \"$(conda shell.posix activate {})\"\n",
+                shell_quote(directory)
+            ),
+            Self::Venv => format!(
+                ". {}\n",
+                shell_quote(directory.join("bin").join("activate"))
+            ),
+            Self::Nix => format!(
+                "export PATH={}:\"$PATH\"\n",
+                shell_quote(directory.join("bin"))
+            ),
+        }
+    }
+}
+
+/// The software environment of a tool instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Environment {
+    /// The kind of the environment.
+    pub kind: EnvironmentKind,
+    /// The path of the file specifying the environment.
+    pub spec: String,
+    /// The hash identifying the environment, computed from its kind and the contents of its specification,
+    /// or `None` if the specification could not be read.
+    pub hash: Option<String>,
+}
+
+impl Environment {
+    /// Creates the environment of the given kind from the specification at the given path.
+    /// The specification is hashed once here, such that it is read only once per invocation.
+    pub fn new(kind: EnvironmentKind, spec: String) -> Self {
+        let hash = read(&spec).ok().map(|content| {
+            let mut hasher = Sha256::new();
+            hasher.update(kind.name());
+            hasher.update([0]);
+            hasher.update(content);
+            format!("{:x}", hasher.finalize())
+        });
+        Self { kind, spec, hash }
+    }
+
+    /// Returns the absolute path of the directory of the environment inside the given cache directory.
+    pub fn directory(&self, cache_directory: &Path) -> TypemakeResult<PathBuf> {
+        let hash = self.hash.as_ref().ok_or_else(|| {
+            TypemakeError::GeneralError(format!(
+                "Could not read the {} environment specification {:?}",
+                self.kind.name(),
+                self.spec
+            ))
+        })?;
+        Ok(std::env::current_dir()?.join(cache_directory).join(format!(
+            "{}-{}",
+            self.kind.name(),
+            &hash[..DIRECTORY_HASH_LENGTH]
+        )))
+    }
+
+    /// Returns the shell commands creating the environment inside the given cache directory, unless it was created already.
+    /// The output of the creation is written into a log next to the environment directory, and leftovers of an interrupted creation are replaced.
+    /// Concurrent creations of the same environment are serialized with `flock`, if it is available.
+    pub fn creation_script(&self, cache_directory: &Path) -> TypemakeResult<String> {
+        let directory = self.directory(cache_directory)?;
+        let spec = std::env::current_dir()?.join(&self.spec);
+        let log_path = directory.with_extension("log");
+        Ok(format!(
+            "mkdir -p {cache_directory} || exit 1
+(
+  if command -v flock > /dev/null; then flock 9; fi
+  if test ! -e {complete_marker}; then
+    echo {creating}
+    rm -rf {directory}
+    if ! ({create_command}) > {log_path} 2>&1; then
+      echo {failed} >&2
+      exit 1
+    fi
+    touch {complete_marker}
+  fi
+) 9> {lock_path} || exit 1
+",
+            cache_directory = shell_quote(directory.parent().unwrap_or(&directory)),
+            complete_marker = shell_quote(directory.with_extension("complete")),
+            creating = shell_quote(format!(
+                "Creating {} environment {:?} from {:?}",
+                self.kind.name(),
+                directory,
+                self.spec
+            )),
+            directory = shell_quote(&directory),
+            create_command = self.kind.create_command(&spec, &directory),
+            log_path = shell_quote(&log_path),
+            failed = shell_quote(format!(
+                "Creating the {} environment from {:?} failed. See {:?} for details.",
+                self.kind.name(),
+                self.spec,
+                log_path
+            )),
+            lock_path = shell_quote(directory.with_extension("lock")),
+        ))
+    }
+
+    /// Returns the shell commands activating the environment inside the given cache directory.
+    pub fn activation(&self, cache_directory: &Path) -> TypemakeResult<String> {
+        Ok(self.kind.activation(&self.directory(cache_directory)?))
+    }
+}
@@ -11,19 +11,27 @@ pub type TypemakeResult<T> = Result<T, TypemakeError>;
 #[derive(Error, Debug)]
 #[allow(clippy::enum_variant_names)]
 pub enum TypemakeError {
-    #[error("Could not parse typefile.")]
+    #[error("Could not parse typefile: {0}")]
     /// An error that occurred in the typefile parser.
     ParserError(String),
 
-    #[error("I/O error.")]
+    #[error("I/O error: {0}")]
     /// An I/O error.
     IoError(#[from] ::std::io::Error),
 
-    #[error("Error while interpreting script.")]
+    #[error("Error while interpreting script: {0}")]
     /// An error that occurred in the script interpreter.
     InterpreterError(#[from] InterpreterError),
 
-    #[error("An error occurred.")]
+    #[error("Serialization error: {0}")]
+    /// An error that occurred while serializing or deserializing data, such as metadata or lock files.
+    SerializationError(#[from] serde_json::Error),
+
+    #[error("Could not load configuration: {0}")]
+    /// An error in the configuration files or the configuration given on the command line.
+    ConfigError(String),
+
+    #[error("An error occurred: {0}")]
     /// An error that does not fit into the other categories.
     GeneralError(String),
 }
@@ -0,0 +1,146 @@
+//! A machine-readable stream of events describing the execution of a workflow, for external monitoring.
+//!
+//! Each event is written as a single line of JSON, with the kind of the event in the field `event`
+//! and the time at which it occurred in seconds since the unix epoch in the field `time`.
+
+use crate::error::TypemakeResult;
+use crate::state::metadata::Benchmark;
+use crate::workflow::Resources;
+use log::warn;
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An event in the execution of a workflow.
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// Typemake started executing the workflow.
+    WorkflowStarted {
+        /// The path of the typefile.
+        typefile: PathBuf,
+        /// The requested targets.
+        targets: Vec<String>,
+        /// The process id of typemake.
+        pid: u32,
+    },
+    /// The workflow graph was built from the tools that could be instantiated.
+    DagBuilt {
+        /// The number of tool instances in the graph.
+        tool_instances: usize,
+        /// The names of the tools that could not be instantiated yet.
+        uninstantiated_tools: Vec<String>,
+    },
+    /// A tool instance needs to be executed to produce the targets.
+    JobQueued {
+        /// The name of the tool instance.
+        instance: String,
+    },
+    /// An attempt of executing a tool instance started.
+    JobStarted {
+        /// The name of the tool instance.
+        instance: String,
+        /// The number of the attempt, starting at one.
+        attempt: u64,
+        /// The resources requested by the attempt.
+        resources: Resources,
+    },
+    /// A tool instance finished successfully.
+    JobFinished {
+        /// The name of the tool instance.
+        instance: String,
+        /// The number of the attempt, starting at one.
+        attempt: u64,
+        /// The resources used by the attempt.
+        benchmark: Option<Benchmark>,
+    },
+    /// An attempt of executing a tool instance failed.
+    JobFailed {
+        /// The name of the tool instance.
+        instance: String,
+        /// The number of the attempt, starting at one.
+        attempt: u64,
+        /// A description of the failure.
+        reason: String,
+        /// True if the instance is attempted again.
+        retrying: bool,
+        /// The resources used by the attempt.
+        benchmark: Benchmark,
+    },
+    /// Typemake stopped executing the workflow.
+    WorkflowFinished {
+        /// True if all targets were produced.
+        success: bool,
+        /// The error that stopped the workflow, if any.
+        error: Option<String>,
+    },
+}
+
+/// An event together with the time at which it occurred.
+#[derive(Serialize)]
+struct TimedEvent<'event> {
+    /// The time of the event in seconds since the unix epoch.
+    time: f64,
+    /// The event.
+    #[serde(flatten)]
+    event: &'event Event,
+}
+
+/// A destination of events.
+/// Clones write to the same destination.
+#[derive(Clone)]
+pub struct EventSink {
+    /// The writer receiving the events.
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl EventSink {
+    /// Opens the given destination for writing events.
+    /// If the destination is a unix socket, the events are sent to it, and otherwise they are appended to the file at the destination.
+    pub fn open(destination: &Path) -> TypemakeResult<Self> {
+        let is_socket = destination
+            .metadata()
+            .map(|metadata| metadata.file_type().is_socket())
+            .unwrap_or(false);
+        let writer: Box<dyn Write + Send> = if is_socket {
+            Box::new(UnixStream::connect(destination)?)
+        } else {
+            Box::new(
+                OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(destination)?,
+            )
+        };
+        Ok(Self {
+            writer: Arc::new(Mutex::new(writer)),
+        })
+    }
+
+    /// Writes the given event as a single line of JSON.
+    /// Failing to write an event does not affect the workflow, so errors are only logged.
+    pub fn emit(&self, event: Event) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let result = serde_json::to_string(&TimedEvent {
+            time,
+            event: &event,
+        })
+        .map_err(std::io::Error::from)
+        .and_then(|line| {
+            let mut writer = self.writer.lock().unwrap();
+            writeln!(writer, "{}", line)?;
+            writer.flush()
+        });
+        if let Err(error) = result {
+            warn!("Could not write event: {}", error);
+        }
+    }
+}
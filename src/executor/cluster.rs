@@ -0,0 +1,272 @@
+//! Execution of tool instances on batch systems, via user-defined commands for submitting, querying and cancelling jobs.
+//!
+//! Each attempt of a tool instance is written into a job script in its staging directory, which is submitted by running
+//! the submit command with the path of the job script as additional argument.
+//! The submit command must print the id of the job as the last line of its output, optionally followed by `;` and further text,
+//! as printed by `sbatch --parsable`.
+//! The placeholders `{name}`, `{attempt}`, `{threads}`, `{memory}` and `{timeout}` in the submit command are replaced
+//! by the name of the tool instance, the number of the attempt and the resources requested by the attempt.
+//!
+//! The status command is run with the id of the job as additional argument, and must print `success` or `failed` if the job terminated.
+//! Any other output means that the job is still queued or running.
+//! Without a status command, the job is considered terminated once its job script wrote the exit status of the tool script,
+//! which requires the staging directory to be on a filesystem shared with the nodes of the batch system.
+//!
+//! The cancel command is run with the id of the job as additional argument when typemake is interrupted.
+//! Without a cancel command, jobs keep running after typemake was interrupted.
+
+use crate::error::{TypemakeError, TypemakeResult};
+use crate::executor::job_script::{run_command, shell_quote, JobScript};
+use crate::executor::{Executor, Job};
+use crate::process::ProcessTermination;
+use crate::workflow::ToolInstance;
+use log::{debug, warn};
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::process::ExitStatus;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The minimum interval between two queries of the status of a job.
+const STATUS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// An executor submitting tool instances as jobs to a batch system, using the configured commands.
+#[derive(Debug, Clone)]
+pub struct ClusterExecutor {
+    /// The command submitting a job script.
+    submit_command: String,
+    /// The command printing the status of a job, if any.
+    status_command: Option<String>,
+    /// The command cancelling a job, if any.
+    cancel_command: Option<String>,
+}
+
+impl ClusterExecutor {
+    /// Creates an executor that submits jobs with the given commands.
+    pub fn new(
+        submit_command: String,
+        status_command: Option<String>,
+        cancel_command: Option<String>,
+    ) -> Self {
+        Self {
+            submit_command,
+            status_command,
+            cancel_command,
+        }
+    }
+}
+
+impl Executor for ClusterExecutor {
+    fn submit(
+        &mut self,
+        tool_instance: &ToolInstance,
+        attempt: u64,
+        log_path: &Path,
+        working_directory: &Path,
+    ) -> TypemakeResult<Box<dyn Job>> {
+        let job_script =
+            JobScript::write(tool_instance, attempt, log_path, working_directory, &[])?;
+        let submit_command = fill_placeholders(&self.submit_command, tool_instance, attempt)?;
+        let output = run_command(&format!(
+            "{} {}",
+            submit_command,
+            shell_quote(&job_script.path)
+        ))?;
+        let id = output
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .and_then(|line| line.split(';').next())
+            .map(|id| id.trim().to_owned())
+            .ok_or_else(|| {
+                TypemakeError::GeneralError(format!(
+                    "Submit command {:?} did not print a job id",
+                    submit_command
+                ))
+            })?;
+        debug!(
+            "Submitted tool instance {:?} as job {}",
+            tool_instance.name, id
+        );
+
+        Ok(Box::new(ClusterJob {
+            id,
+            name: tool_instance.name.clone(),
+            status_command: self.status_command.clone(),
+            cancel_command: self.cancel_command.clone(),
+            job_script,
+            started: SystemTime::now(),
+            start_instant: Instant::now(),
+            last_status_query: None,
+            termination: None,
+        }))
+    }
+}
+
+/// A tool instance submitted as a job to a batch system.
+#[derive(Debug)]
+struct ClusterJob {
+    /// The id of the job as printed by the submit command.
+    id: String,
+    /// The name of the tool instance.
+    name: String,
+    /// The command printing the status of the job, if any.
+    status_command: Option<String>,
+    /// The command cancelling the job, if any.
+    cancel_command: Option<String>,
+    /// The job script, which writes the exit status of the tool script.
+    job_script: JobScript,
+    /// The time at which the job was submitted.
+    started: SystemTime,
+    /// The instant at which the job was submitted, for measuring its wall-clock time.
+    start_instant: Instant,
+    /// The instant at which the status of the job was last queried.
+    last_status_query: Option<Instant>,
+    /// The outcome of the job, once it terminated.
+    termination: Option<ProcessTermination>,
+}
+
+impl ClusterJob {
+    /// Queries if the job terminated, and if so, if it was successful.
+    fn query_status(&self) -> TypemakeResult<Option<bool>> {
+        let status_command = if let Some(status_command) = &self.status_command {
+            status_command
+        } else {
+            return Ok(self
+                .job_script
+                .read_exit_code()?
+                .map(|exit_code| exit_code == 0));
+        };
+
+        match run_command(&format!("{} {}", status_command, shell_quote(&self.id))) {
+            Ok(output) => Ok(match output.trim() {
+                "success" => Some(true),
+                "failed" => Some(false),
+                _ => None,
+            }),
+            Err(error) => {
+                warn!(
+                    "Could not query the status of job {} of tool instance {:?}: {}",
+                    self.id, self.name, error
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Records the termination of the job with the given exit status.
+    fn terminate(&mut self, exit_status: ExitStatus) -> ProcessTermination {
+        let termination = ProcessTermination::unmeasured(
+            exit_status,
+            self.started,
+            self.start_instant.elapsed(),
+            false,
+        );
+        self.termination = Some(termination.clone());
+        termination
+    }
+}
+
+impl Job for ClusterJob {
+    fn try_wait(&mut self) -> TypemakeResult<Option<ProcessTermination>> {
+        if let Some(termination) = &self.termination {
+            return Ok(Some(termination.clone()));
+        }
+        if self
+            .last_status_query
+            .is_some_and(|last_status_query| last_status_query.elapsed() < STATUS_INTERVAL)
+        {
+            return Ok(None);
+        }
+        self.last_status_query = Some(Instant::now());
+
+        let success = if let Some(success) = self.query_status()? {
+            success
+        } else {
+            return Ok(None);
+        };
+        // The batch system may report a failure without the job script having written an exit status, e.g. if the job was killed.
+        let exit_code = self
+            .job_script
+            .read_exit_code()?
+            .unwrap_or(if success { 0 } else { 1 });
+        Ok(Some(self.terminate(ExitStatus::from_raw(exit_code << 8))))
+    }
+
+    fn wait(&mut self) -> TypemakeResult<ProcessTermination> {
+        loop {
+            if let Some(termination) = self.try_wait()? {
+                return Ok(termination);
+            }
+            std::thread::sleep(STATUS_INTERVAL);
+        }
+    }
+
+    fn signal(&mut self, signal: libc::c_int) -> TypemakeResult<()> {
+        if self.termination.is_some() {
+            return Ok(());
+        }
+        if let Some(cancel_command) = &self.cancel_command {
+            if let Err(error) =
+                run_command(&format!("{} {}", cancel_command, shell_quote(&self.id)))
+            {
+                warn!(
+                    "Could not cancel job {} of tool instance {:?}: {}",
+                    self.id, self.name, error
+                );
+            }
+        } else {
+            warn!(
+                "No cancel command given, job {} of tool instance {:?} keeps running",
+                self.id, self.name
+            );
+        }
+        // Cancelled jobs are not queried anymore, since the batch system may not report them as terminated immediately.
+        self.terminate(ExitStatus::from_raw(signal));
+        Ok(())
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.start_instant.elapsed()
+    }
+}
+
+/// Replaces the placeholders in the given submit command by the properties of the given attempt of the tool instance.
+/// Returns an error if the command refers to a resource the attempt does not request.
+fn fill_placeholders(
+    command: &str,
+    tool_instance: &ToolInstance,
+    attempt: u64,
+) -> TypemakeResult<String> {
+    let resources = tool_instance.resources_for_attempt(attempt);
+    let mut command = command
+        .replace("{name}", &tool_instance.name)
+        .replace("{attempt}", &attempt.to_string());
+    for (placeholder, value) in [
+        (
+            "{threads}",
+            resources.threads.map(|threads| threads.to_string()),
+        ),
+        (
+            "{memory}",
+            resources.memory.map(|memory| memory.to_string()),
+        ),
+        (
+            "{timeout}",
+            resources
+                .timeout
+                .map(|timeout| (timeout.ceil() as u64).to_string()),
+        ),
+    ] {
+        if !command.contains(placeholder) {
+            continue;
+        }
+        let value = value.ok_or_else(|| {
+            TypemakeError::GeneralError(format!(
+                "The submit command uses {}, but tool instance {:?} does not request this resource. Use --resources to set a default.",
+                placeholder, tool_instance.name
+            ))
+        })?;
+        command = command.replace(placeholder, &value);
+    }
+    Ok(command)
+}
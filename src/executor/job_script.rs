@@ -0,0 +1,140 @@
+//! Job scripts, which execute the script of a tool instance on a batch system.
+//!
+//! A job script changes into the staging directory of the tool instance, exports the environment variables describing the attempt,
+//! runs the script of the tool instance with its output appended to the log, and writes the exit status of the script into a file.
+//! The exit status file allows to detect the termination of the script on batch systems without job accounting,
+//! if the staging directory is on a filesystem shared with the nodes of the batch system.
+
+use crate::error::{TypemakeError, TypemakeResult};
+use crate::workflow::ToolInstance;
+use log::debug;
+use std::fs::{read_to_string, remove_file, set_permissions, write, File, OpenOptions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// The name of the job script in the staging directory.
+const JOB_SCRIPT_FILE_NAME: &str = ".typemake-job.sh";
+/// The name of the file in the staging directory containing the command running the script of the tool instance.
+const COMMAND_FILE_NAME: &str = ".typemake-command.sh";
+/// The name of the file in the staging directory to which the job script writes the exit status of the tool script.
+const EXIT_STATUS_FILE_NAME: &str = ".typemake-exit-status";
+
+/// A job script written into the staging directory of a tool instance.
+#[derive(Debug, Clone)]
+pub struct JobScript {
+    /// The absolute path of the job script.
+    pub path: PathBuf,
+    /// The absolute path of the file to which the job script writes the exit status of the tool script.
+    pub exit_status_path: PathBuf,
+}
+
+impl JobScript {
+    /// Writes the job script for the given attempt of the tool instance into the working directory, and prepares its log.
+    /// The first attempt truncates the log, while further attempts append to it.
+    /// The given header lines are inserted after the shebang, e.g. to pass directives to the batch system.
+    pub fn write(
+        tool_instance: &ToolInstance,
+        attempt: u64,
+        log_path: &Path,
+        working_directory: &Path,
+        header: &[String],
+    ) -> TypemakeResult<Self> {
+        if attempt <= 1 {
+            File::create(log_path)?;
+        } else {
+            OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(log_path)?;
+        }
+        let current_directory = std::env::current_dir()?;
+        let working_directory = current_directory.join(working_directory);
+        let log_path = current_directory.join(log_path);
+
+        let command_path = working_directory.join(COMMAND_FILE_NAME);
+        let exit_status_path = working_directory.join(EXIT_STATUS_FILE_NAME);
+        let path = working_directory.join(JOB_SCRIPT_FILE_NAME);
+        write(&command_path, &tool_instance.script)?;
+        if exit_status_path.exists() {
+            remove_file(&exit_status_path)?;
+        }
+
+        let resources = tool_instance.resources_for_attempt(attempt);
+        let mut content = format!("#!/bin/bash\n");
+        for line in header {
+            content.push_str(line);
+            content.push('\n');
+        }
+        content.push_str(&format!(
+            "# Attempt {} of tool instance {:?}, submitted by typemake.\ncd {} || exit 1\nexport TYPEMAKE_ATTEMPT={}\n",
+            attempt,
+            tool_instance.name,
+            shell_quote(&working_directory),
+            attempt
+        ));
+        if let Some(threads) = resources.threads {
+            content.push_str(&format!("export TYPEMAKE_THREADS={}\n", threads));
+        }
+        if let Some(memory) = resources.memory {
+            content.push_str(&format!("export TYPEMAKE_MEMORY={}\n", memory));
+        }
+        content.push_str(&format!(
+            "bash {} >> {} 2>&1\nexit_status=$?\necho $exit_status > {}.tmp && mv {}.tmp {}\nexit $exit_status\n",
+            shell_quote(&command_path),
+            shell_quote(&log_path),
+            shell_quote(&exit_status_path),
+            shell_quote(&exit_status_path),
+            shell_quote(&exit_status_path),
+        ));
+        write(&path, content)?;
+        set_permissions(&path, PermissionsExt::from_mode(0o755))?;
+
+        Ok(Self {
+            path,
+            exit_status_path,
+        })
+    }
+
+    /// Returns the exit code of the tool script, if the job script wrote it already.
+    pub fn read_exit_code(&self) -> TypemakeResult<Option<i32>> {
+        if !self.exit_status_path.exists() {
+            return Ok(None);
+        }
+        let exit_code = read_to_string(&self.exit_status_path)?;
+        exit_code.trim().parse().map(Some).map_err(|_| {
+            TypemakeError::GeneralError(format!(
+                "Job script {:?} wrote the invalid exit status {:?}",
+                self.path, exit_code
+            ))
+        })
+    }
+}
+
+/// Runs the given command line with bash and returns its stdout.
+/// Returns an error if the command fails.
+pub fn run_command(command_line: &str) -> TypemakeResult<String> {
+    debug!("Running {:?}", command_line);
+    let output = Command::new("bash")
+        .arg("-c")
+        .arg(command_line)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(TypemakeError::GeneralError(format!(
+            "Command {:?} failed with {}: {}",
+            command_line,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Quotes the given string for use as a single word in a shell command.
+pub fn shell_quote(word: impl AsRef<Path>) -> String {
+    format!(
+        "'{}'",
+        word.as_ref().to_string_lossy().replace('\'', "'\\''")
+    )
+}
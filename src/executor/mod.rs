@@ -0,0 +1,94 @@
+//! Executors run the scripts of tool instances on behalf of the scheduler.
+//!
+//! The scheduler submits each attempt of a tool instance to its executor, which returns a `Job` handle.
+//! The scheduler then polls the job until it terminates, and forwards interrupt signals to it.
+//! Tool instances are executed as local processes by the `LocalExecutor`, or submitted to a batch system by the `ClusterExecutor` or the `SlurmExecutor`.
+
+use crate::error::TypemakeResult;
+use crate::process::{ProcessTermination, ResourceUsage, ToolProcess};
+use crate::workflow::ToolInstance;
+use std::fmt::Debug;
+use std::path::Path;
+use std::time::Duration;
+
+pub mod cluster;
+pub mod job_script;
+pub mod slurm;
+
+/// A backend that executes the scripts of tool instances.
+pub trait Executor {
+    /// Submits the given attempt of executing the script of the given tool instance.
+    /// The script runs in the given working directory, and its stdout and stderr are written to the file at `log_path`.
+    /// The first attempt truncates the log, while further attempts append to it.
+    fn submit(
+        &mut self,
+        tool_instance: &ToolInstance,
+        attempt: u64,
+        log_path: &Path,
+        working_directory: &Path,
+    ) -> TypemakeResult<Box<dyn Job>>;
+}
+
+/// A submitted attempt of executing the script of a tool instance.
+pub trait Job: Debug {
+    /// Returns the outcome of the job if it has terminated.
+    /// If so, all of its output has been written to the log when this function returns.
+    fn try_wait(&mut self) -> TypemakeResult<Option<ProcessTermination>>;
+
+    /// Waits until the job terminates and returns its outcome.
+    fn wait(&mut self) -> TypemakeResult<ProcessTermination>;
+
+    /// Sends the given signal to the job.
+    fn signal(&mut self, signal: libc::c_int) -> TypemakeResult<()>;
+
+    /// Returns the time since the job was submitted.
+    fn elapsed(&self) -> Duration;
+
+    /// Returns the resources currently used by the job, or `None` if they cannot be measured, e.g. on a batch system.
+    fn resource_usage(&self) -> Option<ResourceUsage> {
+        None
+    }
+}
+
+/// The default executor, running the scripts as processes on the local machine.
+#[derive(Debug, Default, Clone)]
+pub struct LocalExecutor;
+
+impl Executor for LocalExecutor {
+    fn submit(
+        &mut self,
+        tool_instance: &ToolInstance,
+        attempt: u64,
+        log_path: &Path,
+        working_directory: &Path,
+    ) -> TypemakeResult<Box<dyn Job>> {
+        Ok(Box::new(ToolProcess::spawn(
+            tool_instance,
+            attempt,
+            log_path,
+            working_directory,
+        )?))
+    }
+}
+
+impl Job for ToolProcess {
+    fn try_wait(&mut self) -> TypemakeResult<Option<ProcessTermination>> {
+        ToolProcess::try_wait(self)
+    }
+
+    fn wait(&mut self) -> TypemakeResult<ProcessTermination> {
+        ToolProcess::wait(self)
+    }
+
+    fn signal(&mut self, signal: libc::c_int) -> TypemakeResult<()> {
+        ToolProcess::signal(self, signal)
+    }
+
+    fn elapsed(&self) -> Duration {
+        ToolProcess::elapsed(self)
+    }
+
+    fn resource_usage(&self) -> Option<ResourceUsage> {
+        ToolProcess::resource_usage(self)
+    }
+}
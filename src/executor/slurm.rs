@@ -0,0 +1,561 @@
+//! Execution of tool instances on the SLURM workload manager.
+//!
+//! Each attempt of a tool instance is written into a job script in its staging directory, with `#SBATCH` directives
+//! requesting the resources of the attempt: `threads` as `--cpus-per-task`, `memory` in megabytes as `--mem` and `timeout` as `--time`.
+//! The output of SLURM itself, like the message about a job exceeding its time limit, is appended to the log of the tool instance.
+//!
+//! Tool instances are submitted in batches whenever the scheduler polls them.
+//! Instances of a batch requesting the same resources are grouped into a job array, whose tasks execute the job scripts of the instances.
+//! If `sbatch` fails, the affected instances are considered as failed, with the error of `sbatch` appended to their logs.
+//!
+//! The states of the jobs are polled with `sacct`, with an interval that grows while no job changes its state.
+//! If job accounting is not available, `squeue` is used instead, and a job is considered terminated
+//! once its job script wrote the exit status of the tool script, which requires a filesystem shared with the compute nodes.
+
+use crate::error::{TypemakeError, TypemakeResult};
+use crate::executor::job_script::{run_command, shell_quote, JobScript};
+use crate::executor::{Executor, Job};
+use crate::process::ProcessTermination;
+use crate::workflow::{Resources, ToolInstance};
+use log::{debug, info, warn};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, write, OpenOptions};
+use std::io::Write;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The interval between two queries of the job states after a job changed its state.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The maximum interval between two queries of the job states, reached by doubling the interval while no job changes its state.
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// The maximum number of tasks of a job array, below the default `MaxArraySize` of SLURM.
+const MAX_ARRAY_SIZE: usize = 1000;
+/// The states in which a SLURM job has terminated.
+const TERMINAL_STATES: [&str; 9] = [
+    "BOOT_FAIL",
+    "CANCELLED",
+    "COMPLETED",
+    "DEADLINE",
+    "FAILED",
+    "NODE_FAIL",
+    "OUT_OF_MEMORY",
+    "PREEMPTED",
+    "TIMEOUT",
+];
+
+/// An executor submitting tool instances as jobs to SLURM.
+#[derive(Debug, Clone)]
+pub struct SlurmExecutor {
+    /// The jobs submitted by this executor, shared with their handles.
+    queue: Rc<RefCell<SlurmQueue>>,
+}
+
+impl SlurmExecutor {
+    /// Creates an executor that writes the scripts of job arrays into the given directory,
+    /// and passes the given additional arguments to `sbatch`, e.g. to select a partition.
+    pub fn new(script_directory: PathBuf, sbatch_arguments: Option<String>) -> Self {
+        Self {
+            queue: Rc::new(RefCell::new(SlurmQueue {
+                script_directory,
+                sbatch_arguments: sbatch_arguments.unwrap_or_default(),
+                jobs: Vec::new(),
+                unsubmitted: Vec::new(),
+                submitted_arrays: 0,
+                poll_interval: MIN_POLL_INTERVAL,
+                next_poll: Instant::now(),
+            })),
+        }
+    }
+}
+
+impl Executor for SlurmExecutor {
+    fn submit(
+        &mut self,
+        tool_instance: &ToolInstance,
+        attempt: u64,
+        log_path: &Path,
+        working_directory: &Path,
+    ) -> TypemakeResult<Box<dyn Job>> {
+        let resources = tool_instance.resources_for_attempt(attempt);
+        let log_path = std::env::current_dir()?.join(log_path);
+        let mut header = vec![
+            format!("#SBATCH --job-name={}", tool_instance.name),
+            format!("#SBATCH --output={}", log_path.to_string_lossy()),
+            "#SBATCH --open-mode=append".to_owned(),
+        ];
+        header.extend(resource_directives(&resources));
+        let job_script = JobScript::write(
+            tool_instance,
+            attempt,
+            &log_path,
+            working_directory,
+            &header,
+        )?;
+
+        let mut queue = self.queue.borrow_mut();
+        let key = queue.jobs.len();
+        queue.jobs.push(SlurmJobEntry {
+            name: tool_instance.name.clone(),
+            resources,
+            log_path,
+            job_script,
+            state: SlurmJobState::Unsubmitted,
+            started: SystemTime::now(),
+            start_instant: Instant::now(),
+        });
+        queue.unsubmitted.push(key);
+        Ok(Box::new(SlurmJob {
+            key,
+            queue: self.queue.clone(),
+        }))
+    }
+}
+
+/// The jobs submitted by a `SlurmExecutor`.
+#[derive(Debug)]
+struct SlurmQueue {
+    /// The directory containing the scripts of job arrays.
+    script_directory: PathBuf,
+    /// Additional arguments passed to `sbatch`.
+    sbatch_arguments: String,
+    /// The jobs, indexed by the keys of their handles.
+    jobs: Vec<SlurmJobEntry>,
+    /// The keys of the jobs that were not submitted to SLURM yet.
+    unsubmitted: Vec<usize>,
+    /// The number of job arrays submitted so far.
+    submitted_arrays: usize,
+    /// The current interval between two queries of the job states.
+    poll_interval: Duration,
+    /// The instant after which the job states are queried next.
+    next_poll: Instant,
+}
+
+/// An attempt of a tool instance, executed as SLURM job.
+#[derive(Debug)]
+struct SlurmJobEntry {
+    /// The name of the tool instance.
+    name: String,
+    /// The resources requested by the attempt.
+    resources: Resources,
+    /// The absolute path of the log of the tool instance.
+    log_path: PathBuf,
+    /// The job script executing the attempt.
+    job_script: JobScript,
+    /// The state of the job.
+    state: SlurmJobState,
+    /// The time at which the attempt was submitted to the executor.
+    started: SystemTime,
+    /// The instant at which the attempt was submitted to the executor, for measuring its wall-clock time.
+    start_instant: Instant,
+}
+
+/// The state of an attempt executed as SLURM job.
+#[derive(Debug)]
+enum SlurmJobState {
+    /// The job was not submitted to SLURM yet.
+    Unsubmitted,
+    /// The job was submitted to SLURM.
+    Submitted {
+        /// The id of the job, of the form `job_task` for tasks of job arrays.
+        id: String,
+        /// The last known SLURM state of the job.
+        slurm_state: String,
+    },
+    /// The job terminated.
+    Terminated(ProcessTermination),
+}
+
+impl SlurmQueue {
+    /// Submits the unsubmitted jobs and queries the states of the submitted jobs, if the poll interval has passed.
+    fn update(&mut self) -> TypemakeResult<()> {
+        self.submit_jobs()?;
+        if Instant::now() < self.next_poll {
+            return Ok(());
+        }
+
+        let changed = self.poll_states()?;
+        self.poll_interval = if changed {
+            MIN_POLL_INTERVAL
+        } else {
+            (self.poll_interval * 2).min(MAX_POLL_INTERVAL)
+        };
+        self.next_poll = Instant::now() + self.poll_interval;
+        Ok(())
+    }
+
+    /// Submits all unsubmitted jobs, grouping jobs with the same resources into job arrays.
+    /// Jobs that cannot be submitted are considered as failed, with the error appended to their logs.
+    fn submit_jobs(&mut self) -> TypemakeResult<()> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for key in self.unsubmitted.drain(..) {
+            // Jobs may have been cancelled before they were submitted.
+            if matches!(self.jobs[key].state, SlurmJobState::Unsubmitted) {
+                groups
+                    .entry(resource_directives(&self.jobs[key].resources).join("\n"))
+                    .or_default()
+                    .push(key);
+            }
+        }
+        if groups.is_empty() {
+            return Ok(());
+        }
+
+        for keys in groups.values() {
+            for keys in keys.chunks(MAX_ARRAY_SIZE) {
+                if let Err(error) = self.submit_chunk(keys) {
+                    for key in keys {
+                        self.jobs[*key].fail_submission(&error);
+                    }
+                }
+            }
+        }
+        self.poll_interval = MIN_POLL_INTERVAL;
+        self.next_poll = Instant::now() + MIN_POLL_INTERVAL;
+        Ok(())
+    }
+
+    /// Submits the given jobs, which request the same resources, as a single job or as a job array.
+    fn submit_chunk(&mut self, keys: &[usize]) -> TypemakeResult<()> {
+        if let [key] = keys {
+            let id = self.sbatch(&self.jobs[*key].job_script.path)?;
+            debug!(
+                "Submitted tool instance {:?} as job {}",
+                self.jobs[*key].name, id
+            );
+            self.set_submitted(*key, id);
+        } else {
+            let array_script = self.write_array_script(keys)?;
+            let id = self.sbatch(&array_script)?;
+            info!(
+                "Submitted {} tool instances as job array {}",
+                keys.len(),
+                id
+            );
+            for (task, key) in keys.iter().enumerate() {
+                self.set_submitted(*key, format!("{}_{}", id, task));
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the given job as submitted with the given id.
+    fn set_submitted(&mut self, key: usize, id: String) {
+        self.jobs[key].state = SlurmJobState::Submitted {
+            id,
+            slurm_state: "PENDING".to_owned(),
+        };
+    }
+
+    /// Writes the script of a job array executing the job scripts of the given jobs, which request the same resources.
+    fn write_array_script(&mut self, keys: &[usize]) -> TypemakeResult<PathBuf> {
+        create_dir_all(&self.script_directory)?;
+        let script_directory = std::env::current_dir()?.join(&self.script_directory);
+        let path = script_directory.join(format!(
+            "array-{}-{}.sh",
+            std::process::id(),
+            self.submitted_arrays
+        ));
+        self.submitted_arrays += 1;
+
+        let mut content = format!(
+            "#!/bin/bash\n#SBATCH --job-name=typemake-array\n#SBATCH --array=0-{}\n#SBATCH --output={}\n",
+            keys.len() - 1,
+            script_directory.join("array-%A_%a.out").to_string_lossy()
+        );
+        for directive in resource_directives(&self.jobs[keys[0]].resources) {
+            content.push_str(&directive);
+            content.push('\n');
+        }
+        content.push_str("job_scripts=(\n");
+        for key in keys {
+            let job = &self.jobs[*key];
+            content.push_str(&format!(
+                "  {} # {}\n",
+                shell_quote(&job.job_script.path),
+                job.name
+            ));
+        }
+        content.push_str(")\nexec bash \"${job_scripts[$SLURM_ARRAY_TASK_ID]}\"\n");
+        write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Submits the given script with `sbatch` and returns the id of the job.
+    fn sbatch(&self, script: &Path) -> TypemakeResult<String> {
+        let output = run_command(&format!(
+            "sbatch --parsable {} {}",
+            self.sbatch_arguments,
+            shell_quote(script)
+        ))?;
+        output
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .and_then(|line| line.split(';').next())
+            .map(|id| id.trim().to_owned())
+            .ok_or_else(|| {
+                TypemakeError::GeneralError(format!(
+                    "sbatch did not print a job id for {:?}",
+                    script
+                ))
+            })
+    }
+
+    /// Queries the states of all submitted jobs and records the terminated ones.
+    /// Returns true if any job changed its state.
+    fn poll_states(&mut self) -> TypemakeResult<bool> {
+        let mut job_ids: Vec<_> = self
+            .jobs
+            .iter()
+            .filter_map(|job| match &job.state {
+                SlurmJobState::Submitted { id, .. } => Some(base_job_id(id).to_owned()),
+                _ => None,
+            })
+            .collect();
+        if job_ids.is_empty() {
+            return Ok(false);
+        }
+        job_ids.sort_unstable();
+        job_ids.dedup();
+        let job_ids = job_ids.join(",");
+
+        match run_command(&format!(
+            "sacct --noheader --parsable2 --allocations --format=JobID,State,ExitCode --jobs={}",
+            job_ids
+        )) {
+            Ok(output) => self.apply_sacct_states(&output),
+            Err(error) => {
+                debug!("Could not query job states with sacct: {}", error);
+                match run_command(&format!(
+                    "squeue --noheader --format='%i|%T' --jobs={}",
+                    job_ids
+                )) {
+                    Ok(output) => self.apply_squeue_states(Some(&output)),
+                    Err(error) => {
+                        warn!("Could not query job states with squeue: {}", error);
+                        self.apply_squeue_states(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Updates the job states from the given output of `sacct`, whose lines are of the form `id|state|exit_code:signal`.
+    fn apply_sacct_states(&mut self, output: &str) -> TypemakeResult<bool> {
+        let states: BTreeMap<_, _> = output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.trim().split('|');
+                Some((fields.next()?, (fields.next()?, fields.next()?)))
+            })
+            .collect();
+
+        let mut changed = false;
+        for job in &mut self.jobs {
+            let id = if let SlurmJobState::Submitted { id, .. } = &job.state {
+                id.clone()
+            } else {
+                continue;
+            };
+            let (state, exit_code) = if let Some(state) = states.get(id.as_str()) {
+                *state
+            } else {
+                continue;
+            };
+            // States may be followed by details, e.g. "CANCELLED by 1000".
+            let state = state.split_whitespace().next().unwrap_or_default();
+            if !TERMINAL_STATES.contains(&state) {
+                changed |= job.set_slurm_state(state);
+                continue;
+            }
+
+            let (code, signal) = exit_code.split_once(':').unwrap_or((exit_code, "0"));
+            let code: i32 = code.parse().unwrap_or(1);
+            let signal: i32 = signal.parse().unwrap_or(0);
+            let exit_status = if signal != 0 {
+                ExitStatus::from_raw(signal)
+            } else if code == 0 && state != "COMPLETED" {
+                ExitStatus::from_raw(1 << 8)
+            } else {
+                ExitStatus::from_raw(code << 8)
+            };
+            if state != "COMPLETED" {
+                warn!(
+                    "Job {} of tool instance {:?} ended in state {}",
+                    id, job.name, state
+                );
+            }
+            job.terminate(exit_status, state == "TIMEOUT");
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// Updates the job states from the given output of `squeue`, whose lines are of the form `id|state`.
+    /// Jobs that are not listed anymore have terminated, with the exit status written by their job script.
+    /// If `squeue` failed, `output` is `None`, and only jobs that wrote their exit status are considered terminated.
+    fn apply_squeue_states(&mut self, output: Option<&str>) -> TypemakeResult<bool> {
+        let states: Option<BTreeMap<_, _>> = output.map(|output| {
+            output
+                .lines()
+                .filter_map(|line| line.trim().split_once('|'))
+                .collect()
+        });
+
+        let mut changed = false;
+        for job in &mut self.jobs {
+            let id = if let SlurmJobState::Submitted { id, .. } = &job.state {
+                id.clone()
+            } else {
+                continue;
+            };
+            if let Some(exit_code) = job.job_script.read_exit_code()? {
+                job.terminate(ExitStatus::from_raw(exit_code << 8), false);
+                changed = true;
+            } else if let Some(state) = states.as_ref().and_then(|states| states.get(id.as_str())) {
+                changed |= job.set_slurm_state(state);
+            } else if states.as_ref().is_some_and(|states| {
+                !states
+                    .keys()
+                    .any(|listed| base_job_id(listed) == base_job_id(&id))
+            }) {
+                // Pending tasks of job arrays are listed in ranges like 123_[4-7], so only jobs missing completely have vanished.
+                warn!(
+                    "Job {} of tool instance {:?} left the queue without writing its exit status",
+                    id, job.name
+                );
+                job.terminate(ExitStatus::from_raw(1 << 8), false);
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Cancels the given job, and considers it as terminated by the given signal.
+    fn cancel(&mut self, key: usize, signal: libc::c_int) {
+        let job = &mut self.jobs[key];
+        match &job.state {
+            SlurmJobState::Terminated(_) => return,
+            SlurmJobState::Unsubmitted => {}
+            SlurmJobState::Submitted { id, .. } => {
+                if let Err(error) = run_command(&format!("scancel {}", shell_quote(id))) {
+                    warn!(
+                        "Could not cancel job {} of tool instance {:?}: {}",
+                        id, job.name, error
+                    );
+                }
+            }
+        }
+        job.terminate(ExitStatus::from_raw(signal), false);
+    }
+}
+
+impl SlurmJobEntry {
+    /// Records the given SLURM state of the job.
+    /// Returns true if it differs from the previous state.
+    fn set_slurm_state(&mut self, state: &str) -> bool {
+        if let SlurmJobState::Submitted { id, slurm_state } = &mut self.state {
+            if slurm_state != state {
+                debug!("Job {} of tool instance {:?} is {}", id, self.name, state);
+                *slurm_state = state.to_owned();
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Records that the job could not be submitted because of the given error, and considers it as failed.
+    fn fail_submission(&mut self, error: &TypemakeError) {
+        warn!("Could not submit tool instance {:?}: {}", self.name, error);
+        let appended = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.log_path)
+            .and_then(|mut log| writeln!(log, "Could not submit the job: {}", error));
+        if let Err(log_error) = appended {
+            warn!(
+                "Could not write to the log {:?}: {}",
+                self.log_path, log_error
+            );
+        }
+        self.terminate(ExitStatus::from_raw(1 << 8), false);
+    }
+
+    /// Records the termination of the job with the given exit status.
+    fn terminate(&mut self, exit_status: ExitStatus, timed_out: bool) {
+        self.state = SlurmJobState::Terminated(ProcessTermination::unmeasured(
+            exit_status,
+            self.started,
+            self.start_instant.elapsed(),
+            timed_out,
+        ));
+    }
+}
+
+/// A handle to an attempt of a tool instance executed as SLURM job.
+#[derive(Debug)]
+struct SlurmJob {
+    /// The index of the job in the queue.
+    key: usize,
+    /// The queue of the executor that submitted the job.
+    queue: Rc<RefCell<SlurmQueue>>,
+}
+
+impl Job for SlurmJob {
+    fn try_wait(&mut self) -> TypemakeResult<Option<ProcessTermination>> {
+        let mut queue = self.queue.borrow_mut();
+        queue.update()?;
+        Ok(match &queue.jobs[self.key].state {
+            SlurmJobState::Terminated(termination) => Some(termination.clone()),
+            _ => None,
+        })
+    }
+
+    fn wait(&mut self) -> TypemakeResult<ProcessTermination> {
+        loop {
+            if let Some(termination) = self.try_wait()? {
+                return Ok(termination);
+            }
+            std::thread::sleep(MIN_POLL_INTERVAL);
+        }
+    }
+
+    fn signal(&mut self, signal: libc::c_int) -> TypemakeResult<()> {
+        self.queue.borrow_mut().cancel(self.key, signal);
+        Ok(())
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.queue.borrow().jobs[self.key].start_instant.elapsed()
+    }
+}
+
+/// Returns the `#SBATCH` directives requesting the given resources.
+fn resource_directives(resources: &Resources) -> Vec<String> {
+    let mut directives = Vec::new();
+    if let Some(threads) = resources.threads {
+        directives.push(format!("#SBATCH --cpus-per-task={}", threads));
+    }
+    if let Some(memory) = resources.memory {
+        directives.push(format!("#SBATCH --mem={}M", memory));
+    }
+    if let Some(timeout) = resources.timeout {
+        let seconds = timeout.ceil() as u64;
+        directives.push(format!(
+            "#SBATCH --time={}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        ));
+    }
+    directives
+}
+
+/// Returns the id of the job array of the given job id, or the job id itself if the job is not part of an array.
+fn base_job_id(id: &str) -> &str {
+    id.split('_').next().unwrap_or(id)
+}
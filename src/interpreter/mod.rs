@@ -1,6 +1,8 @@
 //! The interpreter used to evaluate scripts in the typemake file.
 
 use crate::error::TypemakeResult;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 
 #[cfg(feature = "python")]
 mod python;
@@ -21,6 +23,146 @@ pub trait Interpreter: Sized {
     /// Runs the given code while
     fn run(&mut self, script: &str) -> TypemakeResult<()>;
 
+    /// Evaluates the given expression in the global scope of the interpreter and returns its value.
+    fn evaluate(&mut self, expression: &str) -> TypemakeResult<InterpreterValue>;
+
+    /// Evaluates the given expression declaring input or output artifacts, like `evaluate`.
+    /// Fails if the expression uses a builtin artifact function that the typefile redefined.
+    fn evaluate_artifacts(&mut self, expression: &str) -> TypemakeResult<InterpreterValue>;
+
+    /// Defines a global variable with the given name and value, which is visible to subsequently run scripts and evaluated expressions.
+    fn define(&mut self, name: &str, value: &InterpreterValue) -> TypemakeResult<()>;
+
     /// Returns the version
     fn version(&self) -> TypemakeResult<String>;
 }
+
+/// A value computed by the interpreter, converted into an interpreter-independent representation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpreterValue {
+    /// The absence of a value.
+    None,
+    /// A boolean.
+    Bool(bool),
+    /// An integer.
+    Int(i64),
+    /// A floating point number.
+    Float(f64),
+    /// A string.
+    String(String),
+    /// A sequence of values.
+    List(Vec<InterpreterValue>),
+    /// A mapping from strings to values.
+    Dict(BTreeMap<String, InterpreterValue>),
+}
+
+impl InterpreterValue {
+    /// Returns a short description of the type of this value, for use in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            InterpreterValue::None => "none",
+            InterpreterValue::Bool(_) => "bool",
+            InterpreterValue::Int(_) => "int",
+            InterpreterValue::Float(_) => "float",
+            InterpreterValue::String(_) => "string",
+            InterpreterValue::List(_) => "list",
+            InterpreterValue::Dict(_) => "dict",
+        }
+    }
+}
+
+impl TryFrom<InterpreterValue> for String {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        match value {
+            InterpreterValue::String(string) => Ok(string),
+            other => Err(format!(
+                "expected a string, but got a {}",
+                other.type_name()
+            )),
+        }
+    }
+}
+
+impl TryFrom<InterpreterValue> for bool {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        match value {
+            InterpreterValue::Bool(boolean) => Ok(boolean),
+            other => Err(format!("expected a bool, but got a {}", other.type_name())),
+        }
+    }
+}
+
+impl TryFrom<InterpreterValue> for u64 {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        match value {
+            InterpreterValue::Int(integer) => u64::try_from(integer)
+                .map_err(|_| format!("expected a non-negative int, but got {}", integer)),
+            other => Err(format!("expected an int, but got a {}", other.type_name())),
+        }
+    }
+}
+
+/// Integers are converted into floats.
+/// Float properties are durations and scaling factors, so they must be finite and non-negative.
+impl TryFrom<InterpreterValue> for f64 {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        let float = match value {
+            InterpreterValue::Int(integer) => integer as f64,
+            InterpreterValue::Float(float) => float,
+            other => return Err(format!("expected a float, but got a {}", other.type_name())),
+        };
+        if float.is_finite() && float >= 0.0 {
+            Ok(float)
+        } else {
+            Err(format!(
+                "expected a finite non-negative float, but got {}",
+                float
+            ))
+        }
+    }
+}
+
+/// Implements the conversion from an `InterpreterValue` into an `Option` of the given types.
+/// `None` is interpreted as the absence of a value.
+macro_rules! impl_optional_try_from {
+    ($($value_type:ty),*) => {
+        $(
+            impl TryFrom<InterpreterValue> for Option<$value_type> {
+                type Error = String;
+
+                fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+                    match value {
+                        InterpreterValue::None => Ok(None),
+                        other => <$value_type>::try_from(other).map(Some),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_optional_try_from!(String, u64, f64);
+
+/// A single string is interpreted as a list containing only that string.
+impl TryFrom<InterpreterValue> for Vec<String> {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        match value {
+            InterpreterValue::String(string) => Ok(vec![string]),
+            InterpreterValue::List(list) => list.into_iter().map(String::try_from).collect(),
+            other => Err(format!(
+                "expected a string or a list of strings, but got a {}",
+                other.type_name()
+            )),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 //! An implementation of the typemake interpreter using python.
 
 use crate::error::{TypemakeError, TypemakeResult};
-use crate::interpreter::Interpreter;
+use crate::interpreter::{Interpreter, InterpreterValue};
 use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
 use std::fmt::Formatter;
 use thiserror::Error;
 use std::sync::atomic::AtomicBool;
@@ -10,15 +11,19 @@ use log::{info, error};
 use std::sync::Mutex;
 use lazy_static::lazy_static;
 
+/// Set to true once a python interpreter was created, since python supports being initialised only once.
 static PYTHON_INTERPRETER_CREATED: AtomicBool = AtomicBool::new(false);
 
 lazy_static!{
+    /// The incomplete last line of python's stdout.
     static ref PYTHON_STDOUT: Mutex<String> = Mutex::new(String::new());
+    /// The incomplete last line of python's stderr.
     static ref PYTHON_STDERR: Mutex<String> = Mutex::new(String::new());
 }
 
 //#[pymodule]
 //#[pyo3(name = "typemake_internal")]
+/// Redirects python's stdout and stderr into typemake's log.
 fn redirect_stdout_stderr(py: Python) -> PyResult<()> {
     #[pyfunction]
     fn redirect_stdout(mut message: &str) {
@@ -65,18 +70,90 @@ sys.stderr = RedirectStderr"
     Ok(())
 }
 
+/// Python functions to attach types and flags to inputs and outputs, e.g. `output: nonempty(typed('csv_table', 'result.csv'))`.
+/// Each function accepts either a path or an artifact that already has a type or flags.
+/// They are defined in the private module `_typemake` and made available as python builtins,
+/// such that they do not clash with the definitions of the typefile.
+const BUILTIN_FUNCTIONS: &str = "
+def _artifact(artifact):
+    return dict(artifact) if isinstance(artifact, dict) else {'path': artifact}
+
+def typed(artifact_type, artifact):
+    artifact = _artifact(artifact)
+    artifact['type'] = artifact_type
+    return artifact
+
+def _output_flag(flag):
+    def set_flag(output):
+        output = _artifact(output)
+        output[flag] = True
+        return output
+    set_flag.__name__ = flag
+    return set_flag
+
+nonempty = _output_flag('nonempty')
+temp = _output_flag('temp')
+protected = _output_flag('protected')
+
+def _used_names(expression):
+    import ast
+    return [node.id for node in ast.walk(ast.parse(expression, mode='eval')) if isinstance(node, ast.Name)]
+";
+
+/// The name of the module defining the builtin functions.
+const BUILTIN_MODULE_NAME: &str = "_typemake";
+
+/// The names of the builtin functions.
+const BUILTIN_FUNCTION_NAMES: [&str; 4] = ["typed", "nonempty", "temp", "protected"];
+
+/// Defines typemake's builtin functions in their own module and makes them available as python builtins.
+fn define_builtin_functions(py: Python) -> PyResult<()> {
+    let module = PyModule::from_code(
+        py,
+        BUILTIN_FUNCTIONS,
+        &format!("{}.py", BUILTIN_MODULE_NAME),
+        BUILTIN_MODULE_NAME,
+    )?;
+    let builtins = py.import("builtins")?;
+    for name in BUILTIN_FUNCTION_NAMES {
+        builtins.setattr(name, module.getattr(name)?)?;
+    }
+    Ok(())
+}
+
+/// Raises a `NameError` if the given expression uses a builtin function that the typefile redefined,
+/// since the definition of the typefile would silently replace the builtin function in the expression.
+fn check_builtin_functions_not_shadowed(py: Python, expression: &str) -> PyResult<()> {
+    let main = py.import("__main__")?;
+    let used_names: Vec<String> = py
+        .import(BUILTIN_MODULE_NAME)?
+        .getattr("_used_names")?
+        .call1((expression,))?
+        .extract()?;
+    for name in BUILTIN_FUNCTION_NAMES {
+        if used_names.iter().any(|used_name| used_name == name) && main.hasattr(name)? {
+            return Err(pyo3::exceptions::PyNameError::new_err(format!(
+                "the typefile defines {:?}, which shadows the typemake function of the same name",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// A wrapper around the python interpreter provided by `pyo3`.
 pub struct PythonInterpreter;
 
 impl Interpreter for PythonInterpreter {
     fn new() -> TypemakeResult<Self> {
         if PYTHON_INTERPRETER_CREATED.swap(true, std::sync::atomic::Ordering::Relaxed) {
-            return Err(TypemakeError::GeneralError("Python interpreter was created more than one time, but supports creation only once".into()));
+            return Err(TypemakeError::GeneralError("Python interpreter was created more than one time, but supports creation only once, so only one workflow can be loaded per process".into()));
         }
 
-        // Set up redirection of stdout and stderr through our own logging.
+        // Set up redirection of stdout and stderr through our own logging, and define typemake's builtin functions.
         Python::with_gil(|py| {
-            redirect_stdout_stderr(py)
+            redirect_stdout_stderr(py)?;
+            define_builtin_functions(py)
         }).map_err(PythonInterpreterError::from)?;
         Ok(Self)
     }
@@ -94,6 +171,30 @@ impl Interpreter for PythonInterpreter {
         .map_err(TypemakeError::from)
     }
 
+    fn evaluate(&mut self, expression: &str) -> TypemakeResult<InterpreterValue> {
+        Python::with_gil(|py| {
+            let value = py.eval(expression, None, None)?;
+            convert_python_value(value)
+        })
+        .map_err(PythonInterpreterError::from)
+        .map_err(TypemakeError::from)
+    }
+
+    fn evaluate_artifacts(&mut self, expression: &str) -> TypemakeResult<InterpreterValue> {
+        Python::with_gil(|py| check_builtin_functions_not_shadowed(py, expression))
+            .map_err(PythonInterpreterError::from)?;
+        self.evaluate(expression)
+    }
+
+    fn define(&mut self, name: &str, value: &InterpreterValue) -> TypemakeResult<()> {
+        Python::with_gil(|py| {
+            py.import("__main__")?
+                .setattr(name, python_value(py, value))
+        })
+        .map_err(PythonInterpreterError::from)
+        .map_err(TypemakeError::from)
+    }
+
     fn version(&self) -> TypemakeResult<String> {
         Ok(Python::with_gil(|py| {
             format!("Python {}", py.version()).replace('\n', " ")
@@ -101,6 +202,60 @@ impl Interpreter for PythonInterpreter {
     }
 }
 
+/// Converts a python object into an `InterpreterValue`.
+/// Tuples are converted into lists, and objects of unsupported types result in a `TypeError`.
+fn convert_python_value(value: &PyAny) -> PyResult<InterpreterValue> {
+    // Check for bool before int, since bool is a subclass of int in python.
+    Ok(if value.is_none() {
+        InterpreterValue::None
+    } else if let Ok(boolean) = value.downcast::<PyBool>() {
+        InterpreterValue::Bool(boolean.is_true())
+    } else if value.downcast::<PyLong>().is_ok() {
+        InterpreterValue::Int(value.extract()?)
+    } else if value.downcast::<PyFloat>().is_ok() {
+        InterpreterValue::Float(value.extract()?)
+    } else if value.downcast::<PyString>().is_ok() {
+        InterpreterValue::String(value.extract()?)
+    } else if let Ok(list) = value.downcast::<PyList>() {
+        InterpreterValue::List(list.iter().map(convert_python_value).collect::<PyResult<_>>()?)
+    } else if let Ok(tuple) = value.downcast::<PyTuple>() {
+        InterpreterValue::List(tuple.iter().map(convert_python_value).collect::<PyResult<_>>()?)
+    } else if let Ok(dict) = value.downcast::<PyDict>() {
+        InterpreterValue::Dict(
+            dict.iter()
+                .map(|(key, value)| Ok((key.str()?.extract()?, convert_python_value(value)?)))
+                .collect::<PyResult<_>>()?,
+        )
+    } else {
+        return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+            "cannot convert python value of type {} into a typemake value",
+            value.get_type().name()?
+        )));
+    })
+}
+
+/// Converts an `InterpreterValue` into a python object.
+fn python_value(py: Python, value: &InterpreterValue) -> PyObject {
+    match value {
+        InterpreterValue::None => py.None(),
+        InterpreterValue::Bool(boolean) => boolean.to_object(py),
+        InterpreterValue::Int(integer) => integer.to_object(py),
+        InterpreterValue::Float(float) => float.to_object(py),
+        InterpreterValue::String(string) => string.to_object(py),
+        InterpreterValue::List(list) => {
+            PyList::new(py, list.iter().map(|value| python_value(py, value))).to_object(py)
+        }
+        InterpreterValue::Dict(dict) => {
+            let python_dict = PyDict::new(py);
+            for (key, value) in dict {
+                // Setting an item with a string key cannot fail.
+                python_dict.set_item(key, python_value(py, value)).unwrap();
+            }
+            python_dict.to_object(py)
+        }
+    }
+}
+
 /// A wrapper around the error type of the python interpreter provided by `pyo3`.
 #[derive(Error, Debug)]
 pub struct PythonInterpreterError(#[from] PyErr);
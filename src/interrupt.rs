@@ -0,0 +1,40 @@
+//! Handling of the signals used to interrupt typemake.
+
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// The last interrupt signal received, or zero if none was received.
+static RECEIVED_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+/// The signals that interrupt typemake.
+const INTERRUPT_SIGNALS: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+/// Stores the received signal, to be handled by the scheduler.
+extern "C" fn handle_signal(signal: libc::c_int) {
+    RECEIVED_SIGNAL.store(signal, Ordering::SeqCst);
+}
+
+/// Installs handlers for SIGINT and SIGTERM that record the signal instead of terminating typemake.
+/// Afterwards, `received_signal` reports if typemake was interrupted.
+pub fn install_interrupt_handlers() -> std::io::Result<()> {
+    for &signal in &INTERRUPT_SIGNALS {
+        // Safety: the handler only performs an atomic store, which is async-signal-safe.
+        let previous_handler = unsafe {
+            libc::signal(
+                signal,
+                handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            )
+        };
+        if previous_handler == libc::SIG_ERR {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the interrupt signal received by typemake, if any.
+pub fn received_signal() -> Option<libc::c_int> {
+    match RECEIVED_SIGNAL.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
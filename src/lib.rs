@@ -0,0 +1,48 @@
+//! A workflow engine designed for dynamic, efficient, reproducible and iterative experiment design.
+//!
+//! Besides the `typemake` command line tool, this crate allows to plan and execute workflows programmatically.
+//! A typefile is parsed with [`parse_typefile`], and executed with [`run_workflow`]:
+//!
+//! ```no_run
+//! use typemake::{run_workflow, WorkflowOptions};
+//!
+//! let mut options = WorkflowOptions::default();
+//! options.typefile = "experiments/Typefile".into();
+//! options.targets = vec!["results/summary.txt".to_owned()];
+//! options.scheduler.jobs = 4;
+//! run_workflow(options).expect("Workflow failed");
+//! ```
+//!
+//! To inspect the workflow without executing it, the typefile can be loaded with [`load_workflow`]
+//! and the [`WorkflowGraph`] built by a [`scheduler::Scheduler`].
+//! The scripts of the tool instances are run by an [`Executor`], which defaults to local processes.
+//!
+//! The python interpreter evaluating the typefile can only be created once per process.
+//! Hence [`load_workflow`] and [`run_workflow`] can only be called once, and any further call returns an error.
+//! Programs working with several workflows need to load each of them in a separate process.
+
+#![allow(clippy::useless_format)]
+#![warn(missing_docs)]
+#![warn(clippy::missing_docs_in_private_items)]
+
+pub mod config;
+pub mod container;
+pub mod environment;
+pub mod error;
+pub mod events;
+pub mod executor;
+pub mod interpreter;
+mod interrupt;
+pub mod parser;
+pub mod process;
+pub mod progress;
+pub mod run;
+pub mod scheduler;
+pub mod shell;
+pub mod state;
+pub mod workflow;
+
+pub use crate::executor::Executor;
+pub use crate::parser::{parse_typefile, Typefile};
+pub use crate::run::{load_workflow, run_workflow, WorkflowOptions};
+pub use crate::workflow::WorkflowGraph;
@@ -1,23 +1,25 @@
-//! A workflow engine designed for dynamic, efficient, reproducible and iterative experiment design.
+//! The command line interface of typemake, a thin wrapper around the `typemake` library.
 
 #![allow(clippy::useless_format)]
 #![warn(missing_docs)]
 #![warn(clippy::missing_docs_in_private_items)]
 
-use crate::error::TypemakeResult;
-use crate::typemake::run_typemake_from_cli;
+use crate::commands::run_typemake_from_cli;
+use crate::profile::load_profile;
 use clap::Clap;
 use cli::CliArguments;
-use log::error;
+use log::{error, info};
 use log::LevelFilter;
-use simplelog::{ColorChoice, TermLogger, TerminalMode};
+use simplelog::{
+    ColorChoice, CombinedLogger, Config, SharedLogger, TermLogger, TerminalMode, WriteLogger,
+};
+use std::fs::File;
+use typemake::error::TypemakeResult;
+use typemake::progress::ProgressLogger;
 
 mod cli;
-mod error;
-mod interpreter;
-mod parser;
-mod typemake;
-mod workflow;
+mod commands;
+mod profile;
 
 /// Helper main function that executes the actual main function (`error_main`) and formats any errors it returns.
 fn main() -> TypemakeResult<()> {
@@ -28,20 +30,70 @@ fn main() -> TypemakeResult<()> {
     result
 }
 
+/// The levels of the terminal log, from quietest to most verbose.
+const TERMINAL_LOG_LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+/// The index of the default level of the terminal log in `TERMINAL_LOG_LEVELS`.
+const DEFAULT_TERMINAL_LOG_LEVEL: usize = 3;
+
 /// The actual main function that is allowed to return an error, which is then properly formatted by the `main` function.
 fn error_main() -> TypemakeResult<()> {
+    // Parse cli arguments
+    let mut cli_arguments = CliArguments::parse();
+
     // Init logging
-    TermLogger::init(
-        LevelFilter::Trace,
-        Default::default(),
-        TerminalMode::Stdout,
-        ColorChoice::Auto,
-    )
-    .expect("Could not initialize logging");
+    init_logging(&cli_arguments)?;
 
-    // Parse cli arguments
-    let cli_arguments = CliArguments::parse();
+    // Apply profile
+    if let Some(profile) = load_profile(cli_arguments.profile.as_deref())? {
+        info!(
+            "Using profile {:?}",
+            cli_arguments.profile.as_deref().unwrap_or("default")
+        );
+        profile.apply(&mut cli_arguments);
+    }
 
     // Run typemake
     run_typemake_from_cli(&cli_arguments)
 }
+
+/// Initialises logging to stderr with the verbosity given by the cli arguments, and a full trace to the log file if given.
+/// The terminal log is kept above the live progress display, if it is shown.
+/// If the log file cannot be created, terminal logging is initialised anyway and an error is returned.
+fn init_logging(cli_arguments: &CliArguments) -> TypemakeResult<()> {
+    let terminal_log_level = (DEFAULT_TERMINAL_LOG_LEVEL + cli_arguments.verbose as usize)
+        .saturating_sub(cli_arguments.quiet as usize)
+        .min(TERMINAL_LOG_LEVELS.len() - 1);
+    let mut max_log_level = TERMINAL_LOG_LEVELS[terminal_log_level];
+    let mut loggers: Vec<Box<dyn SharedLogger>> = vec![TermLogger::new(
+        TERMINAL_LOG_LEVELS[terminal_log_level],
+        Config::default(),
+        TerminalMode::Stderr,
+        ColorChoice::Auto,
+    )];
+
+    let mut result = Ok(());
+    match cli_arguments.log_file.as_ref().map(File::create) {
+        Some(Ok(log_file)) => {
+            max_log_level = LevelFilter::Trace;
+            loggers.push(WriteLogger::new(
+                LevelFilter::Trace,
+                Config::default(),
+                log_file,
+            ));
+        }
+        Some(Err(error)) => result = Err(error.into()),
+        None => {}
+    }
+
+    log::set_boxed_logger(Box::new(ProgressLogger::new(CombinedLogger::new(loggers))))
+        .expect("Could not initialize logging");
+    log::set_max_level(max_log_level);
+    result
+}
@@ -1,11 +1,12 @@
 //! The parser for typemake files.
 
+use crate::environment::EnvironmentKind;
 use crate::error::{TypemakeError, TypemakeResult};
-use crate::workflow::{Tool, ToolProperty};
+use crate::workflow::{ArtifactType, Tool, ToolProperty};
 use nom::branch::alt;
-use nom::bytes::complete::{tag, take_until, take_while};
+use nom::bytes::complete::tag;
 use nom::character::complete::{line_ending, space0, space1, not_line_ending};
-use nom::combinator::{fail, iterator, map};
+use nom::combinator::{eof, fail, iterator, map};
 use nom::error::{ErrorKind, ParseError};
 use nom::multi::{fold_many0, many0, many1};
 use nom::sequence::{pair, tuple};
@@ -36,7 +37,7 @@ pub struct ParserError {
     message: String,
 }
 
-impl<'a> Display for ParserError {
+impl Display for ParserError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if self.nom_errors.is_empty() {
             write!(f, "{}", self.message)
@@ -80,15 +81,17 @@ impl<'a> From<&'a str> for ParserError {
 }
 
 /// A parsed typefile.
-#[derive(Default, Eq, PartialEq, Debug, Clone)]
+#[derive(Default, PartialEq, Debug, Clone)]
 pub struct Typefile {
     /// Everything that is not a structure defined by typemake is collected here line-by-line, to be used as "initialising" code for the typefile.
     pub code_lines: String,
     /// The tool definitions in the typefile.
     pub tools: BTreeMap<String, Tool>,
+    /// The artifact type definitions in the typefile.
+    pub types: BTreeMap<String, ArtifactType>,
 }
 
-impl<'a> TryFrom<Vec<ToplevelDefinition>> for Typefile {
+impl TryFrom<Vec<ToplevelDefinition>> for Typefile {
     type Error = Err<ParserError>;
 
     fn try_from(toplevel_definitions: Vec<ToplevelDefinition>) -> Result<Self, Self::Error> {
@@ -100,13 +103,24 @@ impl<'a> TryFrom<Vec<ToplevelDefinition>> for Typefile {
                     result.code_lines.push('\n')
                 }
                 ToplevelDefinition::Tool(tool) => {
-                    if let Some(tool) = result.tools.insert(tool.name.clone(), tool) {
+                    if let Some(tool) = result.tools.insert(tool.name.clone(), *tool) {
                         return Err(Err::Failure(ParserError::from(format!(
                             "Tool already exists: {:?}",
                             tool.name
                         ))));
                     }
                 }
+                ToplevelDefinition::Type(artifact_type) => {
+                    if let Some(artifact_type) = result
+                        .types
+                        .insert(artifact_type.name.clone(), artifact_type)
+                    {
+                        return Err(Err::Failure(ParserError::from(format!(
+                            "Type already exists: {:?}",
+                            artifact_type.name
+                        ))));
+                    }
+                }
             }
         }
         Ok(result)
@@ -119,7 +133,9 @@ enum ToplevelDefinition {
     /// A simple line of code without further meaning to typemake.
     CodeLine(String),
     /// A tool definition.
-    Tool(Tool),
+    Tool(Box<Tool>),
+    /// An artifact type definition.
+    Type(ArtifactType),
 }
 
 /// Parse the typefile at the given path.
@@ -140,7 +156,7 @@ pub fn parse_typefile_content(typefile_content: &str) -> TypemakeResult<Typefile
 
 /// Parse a whole typefile.
 /// This is the root of the nom-part of the parser.
-fn nom_typefile(typefile_definition: &str) -> ParserResult<Typefile> {
+fn nom_typefile(typefile_definition: &str) -> ParserResult<'_, Typefile> {
     let result = fold_many0(parse_toplevel_definition, Vec::new, |mut vec, item| {
         vec.push(item);
         vec
@@ -155,50 +171,112 @@ fn nom_typefile(typefile_definition: &str) -> ParserResult<Typefile> {
 }
 
 /// Parses any definition at the top level of the file, which are all those that don't have any parents.
-fn parse_toplevel_definition(s: &str) -> ParserResult<ToplevelDefinition> {
-    alt((parse_tool_definition, parse_code_line, parse_empty_line))(s)
+fn parse_toplevel_definition(s: &str) -> ParserResult<'_, ToplevelDefinition> {
+    alt((
+        parse_tool_definition,
+        parse_type_definition,
+        parse_code_line,
+        parse_empty_line,
+    ))(s)
 }
 
 /// Parses a tool definition, completely with all entries.
 /// A tool definition is started by `tool <name>:` and followed by zero or more indented lines with further properties.
-fn parse_tool_definition(s: &str) -> ParserResult<ToplevelDefinition> {
-    // Parse header
-    let (s, header) = tuple((
-        tag("tool"),
-        space1,
-        identifier,
-        tag(":"),
-        space0,
-        many1(line_ending),
-    ))(s)?;
+fn parse_tool_definition(s: &str) -> ParserResult<'_, ToplevelDefinition> {
+    let (s, name) = parse_definition_header("tool")(s)?;
     let mut tool = Tool {
-        name: header.2.to_owned(),
+        name: name.to_owned(),
         ..Default::default()
     };
+    let (s, _) = parse_definition_properties(s, &mut tool, "tool", parse_tool_property)?;
+
+    let environment_properties = tool.environment_properties();
+    if environment_properties.len() > 1 {
+        return Err(nom::Err::Failure(ParserError::from(format!(
+            "Tool {:?} declares the environments {}, but at most one environment is allowed per tool.",
+            tool.name,
+            environment_properties.join(", ")
+        ))));
+    }
+    // Virtual environments and nix out-links refer to paths on the host that do not exist inside containers.
+    if !tool.container.is_empty() {
+        if let Some(environment) = environment_properties
+            .iter()
+            .find(|environment| **environment != EnvironmentKind::Conda.name())
+        {
+            return Err(nom::Err::Failure(ParserError::from(format!(
+                "Tool {:?} declares a {} environment and a container, but only conda environments can be used inside containers.",
+                tool.name, environment
+            ))));
+        }
+    }
+    Ok((s, ToplevelDefinition::Tool(Box::new(tool))))
+}
+
+/// Parses an artifact type definition, completely with all entries.
+/// A type definition is started by `type <name>:` and followed by zero or more indented lines with further properties.
+fn parse_type_definition(s: &str) -> ParserResult<'_, ToplevelDefinition> {
+    let (s, name) = parse_definition_header("type")(s)?;
+    let mut artifact_type = ArtifactType {
+        name: name.to_owned(),
+        ..Default::default()
+    };
+    let (s, _) =
+        parse_definition_properties(s, &mut artifact_type, "type", parse_type_property)?;
+    Ok((s, ToplevelDefinition::Type(artifact_type)))
+}
+
+/// Parses the header `<keyword> <name>:` of a definition and returns the name.
+fn parse_definition_header<'keyword>(
+    keyword: &'keyword str,
+) -> impl 'keyword + for<'a> Fn(&'a str) -> ParserResult<'a, &'a str> {
+    move |s: &str| {
+        let (s, header) = tuple((
+            tag(keyword),
+            space1,
+            identifier,
+            tag(":"),
+            space0,
+            many1(line_ending),
+        ))(s)?;
+        Ok((s, header.2))
+    }
+}
 
+/// Parses the properties of a definition and applies them to `target`.
+/// `property_parser` creates a parser for a single property from the indentation of the properties.
+fn parse_definition_properties<'a, Target, PropertyParser>(
+    s: &'a str,
+    target: &mut Target,
+    definition_kind: &str,
+    property_parser: impl Fn(&'a str) -> PropertyParser,
+) -> ParserResult<'a, ()>
+where
+    PropertyParser: Fn(&'a str) -> ParserResult<'a, ParseSetter<'a, Target>>,
+{
     // Parse properties.
     // Check for indentation, but do not cut it off.
     // This is to avoid the special case of the first line being unindented in the properties parser.
     let (_, indentation) = space0(s)?;
     let s = if indentation.is_empty() {
-        // If the first non-empty line after the header is not indented, the rule has no properties.
+        // If the first non-empty line after the header is not indented, the definition has no properties.
         s
     } else {
-        let mut tool_property_iterator = iterator(s, parse_tool_property(indentation));
-        for tool_property in &mut tool_property_iterator {
-            tool_property(&mut tool)?;
+        let mut property_iterator = iterator(s, property_parser(indentation));
+        for property in &mut property_iterator {
+            property(target)?;
         }
-        tool_property_iterator.finish()?.0
+        property_iterator.finish()?.0
     };
 
-    // assert unindented line or end of file after tool definition, to ensure that indentation errors are discovered correctly.
+    // assert unindented line or end of file after the definition, to ensure that indentation errors are discovered correctly.
     let (s, _) = many0(line_ending)(s)?;
     if !s.is_empty() && (s.starts_with('\t') || s.starts_with(' ')) {
-        // Found indented line after end of tool definition.
-        return Err(nom::Err::Failure(ParserError::from(format!("Found an indented line after the end of a tool definition. This means that either after the tool definition, there is an indented line that should not be indented, or the indentation of the tool definition is inconsistent."))));
+        // Found indented line after end of the definition.
+        return Err(nom::Err::Failure(ParserError::from(format!("Found an indented line after the end of a {kind} definition. This means that either after the {kind} definition, there is an indented line that should not be indented, or the indentation of the {kind} definition is inconsistent.", kind = definition_kind))));
     }
 
-    Ok((s, ToplevelDefinition::Tool(tool)))
+    Ok((s, ()))
 }
 
 // fn tool_assigner<'a, PreliminaryType, FinalType>()
@@ -217,19 +295,62 @@ where
         // Parse specific property.
         alt((
             parse_specific_tool_property("interpreter", indentation, |tool| &mut tool.script),
+            parse_specific_tool_property("shell", indentation, |tool| &mut tool.shell),
+            parse_specific_tool_property("input", indentation, |tool| &mut tool.input),
+            parse_specific_tool_property("output", indentation, |tool| &mut tool.output),
+            parse_specific_tool_property("log", indentation, |tool| &mut tool.log),
+            parse_specific_tool_property("threads", indentation, |tool| &mut tool.threads),
+            parse_specific_tool_property("memory", indentation, |tool| &mut tool.memory),
+            parse_specific_tool_property("timeout", indentation, |tool| &mut tool.timeout),
+            parse_specific_tool_property("retries", indentation, |tool| &mut tool.retries),
+            parse_specific_tool_property("retry_backoff", indentation, |tool| {
+                &mut tool.retry_backoff
+            }),
+            parse_specific_tool_property("retry_scaling", indentation, |tool| {
+                &mut tool.retry_scaling
+            }),
+            parse_specific_tool_property("container", indentation, |tool| {
+                &mut tool.container
+            }),
+            parse_specific_tool_property("conda", indentation, |tool| &mut tool.conda),
+            parse_specific_tool_property("venv", indentation, |tool| &mut tool.venv),
+            parse_specific_tool_property("nix", indentation, |tool| &mut tool.nix),
+            parse_tool_flag("checkpoint", |tool| &mut tool.checkpoint),
             fail,
         ))(s)
     }
 }
 
+/// Parses a property of an artifact type.
+fn parse_type_property<'indentation, 'result>(
+    indentation: &'indentation str,
+) -> impl 'result + for<'a> Fn(&'a str) -> ParserResult<'a, ParseSetter<'result, ArtifactType>>
+where
+    'indentation: 'result,
+{
+    move |s: &str| {
+        // Skip whitespace-only lines and check for indentation. If there is none, the type definition is done.
+        let (s, _) = pair(many0(pair(space0, many1(line_ending))), tag(indentation))(s)?;
+
+        // Parse specific property.
+        alt((
+            parse_specific_property("validator", indentation, |artifact_type: &mut ArtifactType| {
+                &mut artifact_type.validator
+            }),
+            fail,
+        ))(s)
+    }
+}
+
+/// A pointer to a function that sets a property value of a definition.
+type ParseSetter<'a, Target> = Box<dyn 'a + FnOnce(&mut Target) -> ParserResultWithoutInput<()>>;
 /// A pointer to a function that sets a property value of a tool.
-type ParseToolSetter<'a> = Box<dyn 'a + FnOnce(&mut Tool) -> ParserResultWithoutInput<()>>;
+type ParseToolSetter<'a> = ParseSetter<'a, Tool>;
 
-/// Parses the interpreter property of a tool.
+/// Parses the given property of a tool.
 fn parse_specific_tool_property<
     'indentation,
     'property_name,
-    'input,
     'result,
     ToolPropertyPreliminaryType: PartialEq,
     ToolPropertyFinalType: PartialEq,
@@ -238,6 +359,27 @@ fn parse_specific_tool_property<
     indentation: &'indentation str,
     tool_property_accessor: impl 'result + for<'tool_property_accessor> Fn(&'tool_property_accessor mut Tool) -> &'tool_property_accessor mut ToolProperty<ToolPropertyPreliminaryType, ToolPropertyFinalType> + Clone,
 ) -> impl 'result + for<'a> FnMut(&'a str) -> ParserResult<'a, ParseToolSetter<'result>>
+where
+    'property_name: 'result,
+    'indentation: 'result,
+{
+    parse_specific_property(property_name, indentation, tool_property_accessor)
+}
+
+/// Parses the given property of a tool or another definition with properties.
+fn parse_specific_property<
+    'indentation,
+    'property_name,
+    'input,
+    'result,
+    Target,
+    ToolPropertyPreliminaryType: PartialEq,
+    ToolPropertyFinalType: PartialEq,
+>(
+    property_name: &'property_name str,
+    indentation: &'indentation str,
+    tool_property_accessor: impl 'result + for<'tool_property_accessor> Fn(&'tool_property_accessor mut Target) -> &'tool_property_accessor mut ToolProperty<ToolPropertyPreliminaryType, ToolPropertyFinalType> + Clone,
+) -> impl 'result + for<'a> FnMut(&'a str) -> ParserResult<'a, ParseSetter<'result, Target>>
 where
     'property_name: 'result,
     'indentation: 'result,
@@ -294,16 +436,46 @@ where
     }
 }
 
+/// Parses a flag of a tool.
+/// A flag is a line that contains only the name of the flag.
+fn parse_tool_flag<'flag_name, 'result>(
+    flag_name: &'flag_name str,
+    tool_flag_accessor: impl 'result + for<'tool_flag_accessor> Fn(&'tool_flag_accessor mut Tool) -> &'tool_flag_accessor mut bool + Clone,
+) -> impl 'result + for<'a> FnMut(&'a str) -> ParserResult<'a, ParseToolSetter<'result>>
+where
+    'flag_name: 'result,
+{
+    move |s: &str| {
+        let (s, _) = tuple((tag(flag_name), space0, alt((line_ending, eof))))(s)?;
+
+        let tool_flag_accessor = tool_flag_accessor.clone();
+        Ok((
+            s,
+            Box::new(move |tool| {
+                let tool_flag = tool_flag_accessor(tool);
+                if *tool_flag {
+                    return Err(nom::Err::Failure(ParserError::from(format!(
+                        "Found a duplicate definition of {:?} within the same tool.",
+                        flag_name
+                    ))));
+                }
+                *tool_flag = true;
+                Ok(())
+            }),
+        ))
+    }
+}
+
 /// Parse a line as a piece of code.
 /// This is the fallback in case the line is of no other type.
-fn parse_code_line(s: &str) -> ParserResult<ToplevelDefinition> {
+fn parse_code_line(s: &str) -> ParserResult<'_, ToplevelDefinition> {
     map(take_line_disallow_empty, |code_line: &str| {
         ToplevelDefinition::CodeLine(code_line.to_owned())
     })(s)
 }
 
 /// Parses an empty line.
-fn parse_empty_line(s: &str) -> ParserResult<ToplevelDefinition> {
+fn parse_empty_line(s: &str) -> ParserResult<'_, ToplevelDefinition> {
     map(pair(space0, line_ending), |_| {
         ToplevelDefinition::CodeLine("".to_owned())
     })(s)
@@ -311,7 +483,7 @@ fn parse_empty_line(s: &str) -> ParserResult<ToplevelDefinition> {
 
 /// Take a full line of output, being robust against different line endings as well as a last line without line ending.
 /// If the line taken is empty, return an error.
-fn take_line_disallow_empty(s: &str) -> ParserResult<&str> {
+fn take_line_disallow_empty(s: &str) -> ParserResult<'_, &str> {
     let line = not_line_ending(s)?;
     if line.1.is_empty() {
         return fail(line.0);
@@ -326,7 +498,7 @@ fn take_line_disallow_empty(s: &str) -> ParserResult<&str> {
 
 /// Take a full line of output, being robust against different line endings as well as a last line without line ending.
 /// If the line taken is empty, just return an empty `str`.
-fn take_line_allow_empty(s: &str) -> ParserResult<&str> {
+fn take_line_allow_empty(s: &str) -> ParserResult<'_, &str> {
     let line = not_line_ending(s)?;
     let s = match line_ending::<_, ParserError>(line.0) {
         Ok((s, _)) => s,
@@ -350,9 +522,9 @@ where
 
 /// Check if the current line in `s` is indented by at least `shallow_indentation` plus at least one space or tab character.
 /// If yes, return the complete indentation of the line, including `shallow_indentation`, if no, return `None`.
-fn check_for_deeper_indentation<'input, 'indentation>(
+fn check_for_deeper_indentation<'input>(
     s: &'input str,
-    shallow_indentation: &'indentation str,
+    shallow_indentation: &str,
 ) -> Option<&'input str> {
     if let Ok((_, deep_indentation)) = space0::<_, ParserError>(s) {
         if deep_indentation.starts_with(shallow_indentation)
@@ -1,5 +1,5 @@
 use crate::parser::{parse_typefile_content, Typefile};
-use crate::workflow::Tool;
+use crate::workflow::{ArtifactType, Tool};
 
 #[test]
 fn test_empty_typefile() {
@@ -144,3 +144,219 @@ fn test_wrong_tool_property_indentation() {
     )
     .unwrap_err();
 }
+
+#[test]
+fn test_tool_input_output_definition() {
+    assert_eq!(
+        parse_typefile_content(
+            "tool mytool:\n  input: [\"a.txt\", \"b.txt\"]\n  output:\n    \"c.txt\"\n  interpreter: \"cat a.txt b.txt > c.txt\"\n"
+        )
+        .unwrap(),
+        Typefile {
+            code_lines: "".into(),
+            tools: [(
+                "mytool".to_owned(),
+                Tool {
+                    name: "mytool".to_string(),
+                    script: "\"cat a.txt b.txt > c.txt\"".into(),
+                    input: "[\"a.txt\", \"b.txt\"]".into(),
+                    output: "\"c.txt\"".into(),
+                    ..Default::default()
+                }
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_tool_checkpoint_flag() {
+    assert_eq!(
+        parse_typefile_content("tool mytool:\n  checkpoint\n  output: \"a.txt\"\nabc").unwrap(),
+        Typefile {
+            code_lines: "abc\n".into(),
+            tools: [(
+                "mytool".to_owned(),
+                Tool {
+                    name: "mytool".to_string(),
+                    output: "\"a.txt\"".into(),
+                    checkpoint: true,
+                    ..Default::default()
+                }
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_duplicate_tool_checkpoint_flag() {
+    parse_typefile_content("tool mytool:\n  checkpoint\n  checkpoint\n").unwrap_err();
+}
+
+#[test]
+fn test_tool_log_definition() {
+    assert_eq!(
+        parse_typefile_content("tool mytool:\n  log: 'logs/mytool.log'\n").unwrap(),
+        Typefile {
+            code_lines: "".into(),
+            tools: [(
+                "mytool".to_owned(),
+                Tool {
+                    name: "mytool".to_string(),
+                    log: "'logs/mytool.log'".into(),
+                    ..Default::default()
+                }
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_tool_resource_and_retry_definition() {
+    assert_eq!(
+        parse_typefile_content(
+            "tool mytool:\n  threads: 4\n  memory: 1024\n  timeout: 60\n  retries: 2\n  retry_backoff: 1.5\n  retry_scaling: 2\n"
+        )
+        .unwrap(),
+        Typefile {
+            code_lines: "".into(),
+            tools: [(
+                "mytool".to_owned(),
+                Tool {
+                    name: "mytool".to_string(),
+                    threads: "4".into(),
+                    memory: "1024".into(),
+                    timeout: "60".into(),
+                    retries: "2".into(),
+                    retry_backoff: "1.5".into(),
+                    retry_scaling: "2".into(),
+                    ..Default::default()
+                }
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_tool_shell_definition() {
+    assert_eq!(
+        parse_typefile_content("tool mytool:\n  shell: 'python'\n  interpreter: 'print(1)'\n")
+            .unwrap(),
+        Typefile {
+            code_lines: "".into(),
+            tools: [(
+                "mytool".to_owned(),
+                Tool {
+                    name: "mytool".to_string(),
+                    shell: "'python'".into(),
+                    script: "'print(1)'".into(),
+                    ..Default::default()
+                }
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_tool_container_definition() {
+    assert_eq!(
+        parse_typefile_content("tool mytool:\n  container: 'ubuntu:22.04'\n").unwrap(),
+        Typefile {
+            code_lines: "".into(),
+            tools: [(
+                "mytool".to_owned(),
+                Tool {
+                    name: "mytool".to_string(),
+                    container: "'ubuntu:22.04'".into(),
+                    ..Default::default()
+                }
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_tool_environment_definition() {
+    assert_eq!(
+        parse_typefile_content("tool mytool:\n  conda: 'envs/mytool.yaml'\n").unwrap(),
+        Typefile {
+            code_lines: "".into(),
+            tools: [(
+                "mytool".to_owned(),
+                Tool {
+                    name: "mytool".to_string(),
+                    conda: "'envs/mytool.yaml'".into(),
+                    ..Default::default()
+                }
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            ..Default::default()
+        }
+    );
+    parse_typefile_content("tool mytool:\n  venv: 'requirements.txt'\n  nix: 'default.nix'\n")
+        .unwrap_err();
+}
+
+#[test]
+fn test_type_definition() {
+    assert_eq!(
+        parse_typefile_content(
+            "type csv_table:\n  validator: lambda path: path.endswith('.csv')\n\ntype opaque:\nabc"
+        )
+        .unwrap(),
+        Typefile {
+            code_lines: "abc\n".into(),
+            types: [
+                (
+                    "csv_table".to_owned(),
+                    ArtifactType {
+                        name: "csv_table".to_string(),
+                        validator: "lambda path: path.endswith('.csv')".into(),
+                    }
+                ),
+                (
+                    "opaque".to_owned(),
+                    ArtifactType {
+                        name: "opaque".to_string(),
+                        ..Default::default()
+                    }
+                )
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_duplicate_type_definition() {
+    parse_typefile_content("type a:\ntype a:\n").unwrap_err();
+    parse_typefile_content("type a:\n  validator: x\n  validator: y\n").unwrap_err();
+}
@@ -0,0 +1,389 @@
+//! Execution of tool instances as local processes.
+
+use crate::error::TypemakeResult;
+use crate::state::metadata::Benchmark;
+use crate::workflow::ToolInstance;
+use log::{error, info, warn};
+use std::cell::RefCell;
+use std::fs::{read_dir, read_to_string, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The unit of `ru_maxrss` in bytes.
+#[cfg(target_os = "macos")]
+const MAX_RSS_UNIT: u64 = 1;
+/// The unit of `ru_maxrss` in bytes.
+#[cfg(not(target_os = "macos"))]
+const MAX_RSS_UNIT: u64 = 1024;
+
+/// The outcome of a terminated tool process.
+#[derive(Debug, Clone)]
+pub struct ProcessTermination {
+    /// The exit status of the process.
+    pub exit_status: ExitStatus,
+    /// The resources used by the process.
+    pub benchmark: Benchmark,
+    /// True if the process was killed because it exceeded its timeout.
+    pub timed_out: bool,
+}
+
+impl ProcessTermination {
+    /// Creates the outcome of a script whose resource usage was not measured, e.g. because it ran on a batch system.
+    /// Only the wall-clock time since the given start is recorded.
+    pub fn unmeasured(
+        exit_status: ExitStatus,
+        started: SystemTime,
+        wall_time: Duration,
+        timed_out: bool,
+    ) -> Self {
+        Self {
+            exit_status,
+            benchmark: Benchmark {
+                started: started
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0),
+                wall_time: wall_time.as_secs_f64(),
+                user_time: 0.0,
+                system_time: 0.0,
+                max_rss: 0,
+                success: exit_status.success() && !timed_out,
+            },
+            timed_out,
+        }
+    }
+}
+
+/// The resources currently used by a running script.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceUsage {
+    /// The number of CPU cores used on average since the previous measurement.
+    pub cpu_cores: f64,
+    /// The resident set size in bytes.
+    pub rss: u64,
+}
+
+/// A running script of a tool instance.
+/// The output of the script is written into a log file and forwarded line-by-line to typemake's log, prefixed with the name of the instance.
+/// The script runs in its own process group, such that it can be killed together with all its children.
+#[derive(Debug)]
+pub struct ToolProcess {
+    /// The process executing the script.
+    child: Child,
+    /// The threads forwarding stdout and stderr of the process.
+    output_threads: Vec<JoinHandle<std::io::Result<()>>>,
+    /// The time at which the process was started.
+    started: SystemTime,
+    /// The instant at which the process was started, for measuring its wall-clock time.
+    start_instant: Instant,
+    /// The time after which the process is killed.
+    timeout: Option<Duration>,
+    /// True if the process was killed because it exceeded its timeout.
+    timed_out: bool,
+    /// The outcome of the process, once it was reaped.
+    termination: Option<ProcessTermination>,
+    /// The instant and the CPU time in seconds of the previous measurement of the resource usage.
+    last_usage_measurement: RefCell<Option<(Instant, f64)>>,
+}
+
+impl ToolProcess {
+    /// Starts the given attempt of executing the script of the given tool instance with bash in the given working directory,
+    /// capturing its stdout and stderr into the file at `log_path`.
+    /// The first attempt truncates the log, while further attempts append to it.
+    ///
+    /// The resources requested by the attempt are passed to the script via the environment variables
+    /// `TYPEMAKE_ATTEMPT`, `TYPEMAKE_THREADS` and `TYPEMAKE_MEMORY`.
+    pub fn spawn(
+        tool_instance: &ToolInstance,
+        attempt: u64,
+        log_path: &Path,
+        working_directory: &Path,
+    ) -> TypemakeResult<Self> {
+        let log_file = if attempt <= 1 {
+            File::create(log_path)?
+        } else {
+            OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(log_path)?
+        };
+        let log_file = Arc::new(Mutex::new(log_file));
+        let resources = tool_instance.resources_for_attempt(attempt);
+
+        let mut command = Command::new("bash");
+        command
+            .arg("-c")
+            .arg(&tool_instance.script)
+            .current_dir(working_directory)
+            .env("TYPEMAKE_ATTEMPT", attempt.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0);
+        if let Some(threads) = resources.threads {
+            command.env("TYPEMAKE_THREADS", threads.to_string());
+        }
+        if let Some(memory) = resources.memory {
+            command.env("TYPEMAKE_MEMORY", memory.to_string());
+        }
+
+        let started = SystemTime::now();
+        let start_instant = Instant::now();
+        let mut child = command.spawn()?;
+
+        let output_threads = vec![
+            spawn_output_thread(
+                child.stdout.take().unwrap(),
+                tool_instance.name.clone(),
+                log_file.clone(),
+                false,
+            ),
+            spawn_output_thread(
+                child.stderr.take().unwrap(),
+                tool_instance.name.clone(),
+                log_file,
+                true,
+            ),
+        ];
+
+        Ok(Self {
+            child,
+            output_threads,
+            started,
+            start_instant,
+            timeout: resources
+                .timeout
+                .map(|timeout| Duration::try_from_secs_f64(timeout).unwrap_or(Duration::MAX)),
+            timed_out: false,
+            termination: None,
+            last_usage_measurement: RefCell::new(None),
+        })
+    }
+
+    /// Returns the outcome of the process if it has terminated.
+    /// If so, all of its output has been written to the log when this function returns.
+    ///
+    /// If the process exceeded its timeout, its process group is killed.
+    pub fn try_wait(&mut self) -> TypemakeResult<Option<ProcessTermination>> {
+        if let Some(timeout) = self.timeout {
+            if !self.timed_out
+                && self.termination.is_none()
+                && self.start_instant.elapsed() > timeout
+            {
+                warn!(
+                    "Killing process group {} after exceeding its timeout of {:.2}s",
+                    self.child.id(),
+                    timeout.as_secs_f64()
+                );
+                self.signal(libc::SIGKILL)?;
+                self.timed_out = true;
+            }
+        }
+
+        self.wait_with_options(libc::WNOHANG)
+    }
+
+    /// Returns the time since the process was started.
+    pub fn elapsed(&self) -> Duration {
+        self.start_instant.elapsed()
+    }
+
+    /// Measures the resources currently used by all processes in the process group of the script.
+    /// The CPU usage is averaged since the previous measurement, or since the start of the process for the first one.
+    /// Returns `None` if the process has terminated or `/proc` is not available.
+    pub fn resource_usage(&self) -> Option<ResourceUsage> {
+        if self.termination.is_some() {
+            return None;
+        }
+        let (cpu_time, rss) = process_group_usage(self.child.id())?;
+        let now = Instant::now();
+        let (previous_instant, previous_cpu_time) = self
+            .last_usage_measurement
+            .replace(Some((now, cpu_time)))
+            .unwrap_or((self.start_instant, 0.0));
+        let interval = now.duration_since(previous_instant).as_secs_f64();
+        // The CPU time of processes that terminated without being reaped by the process group is lost, so the difference may be negative.
+        let cpu_cores = if interval > 0.0 {
+            ((cpu_time - previous_cpu_time) / interval).max(0.0)
+        } else {
+            0.0
+        };
+        Some(ResourceUsage { cpu_cores, rss })
+    }
+
+    /// Sends the given signal to all processes in the process group of the script.
+    pub fn signal(&self, signal: libc::c_int) -> TypemakeResult<()> {
+        // Safety: kill has no memory safety requirements.
+        if unsafe { libc::kill(-(self.child.id() as libc::pid_t), signal) } < 0 {
+            let error = std::io::Error::last_os_error();
+            // The process group may have terminated already.
+            if error.raw_os_error() != Some(libc::ESRCH) {
+                return Err(error.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits until the process terminates and returns its outcome.
+    pub fn wait(&mut self) -> TypemakeResult<ProcessTermination> {
+        Ok(self
+            .wait_with_options(0)?
+            .expect("Blocking wait returned without the process terminating"))
+    }
+
+    /// Reaps the process using `wait4` with the given options, measuring its resource usage.
+    /// Returns `None` if `WNOHANG` is given and the process has not terminated yet.
+    fn wait_with_options(
+        &mut self,
+        options: libc::c_int,
+    ) -> TypemakeResult<Option<ProcessTermination>> {
+        if let Some(termination) = &self.termination {
+            return Ok(Some(termination.clone()));
+        }
+
+        let mut status = 0;
+        // Safety: rusage is a plain C struct for which all zeroes is a valid value.
+        let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+        let pid = loop {
+            // Safety: the pointers passed to wait4 point to valid, writable memory.
+            let pid = unsafe {
+                libc::wait4(
+                    self.child.id() as libc::pid_t,
+                    &mut status,
+                    options,
+                    &mut rusage,
+                )
+            };
+            if pid >= 0 {
+                break pid;
+            }
+
+            let error = std::io::Error::last_os_error();
+            if error.kind() != std::io::ErrorKind::Interrupted {
+                return Err(error.into());
+            }
+        };
+        if pid == 0 {
+            return Ok(None);
+        }
+
+        let wall_time = self.start_instant.elapsed().as_secs_f64();
+        self.join_output_threads()?;
+        let exit_status = ExitStatus::from_raw(status);
+        let termination = ProcessTermination {
+            exit_status,
+            benchmark: Benchmark {
+                started: self
+                    .started
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0),
+                wall_time,
+                user_time: timeval_to_seconds(rusage.ru_utime),
+                system_time: timeval_to_seconds(rusage.ru_stime),
+                max_rss: rusage.ru_maxrss.max(0) as u64 * MAX_RSS_UNIT,
+                success: exit_status.success() && !self.timed_out,
+            },
+            timed_out: self.timed_out,
+        };
+        self.termination = Some(termination.clone());
+        Ok(Some(termination))
+    }
+
+    /// Waits until all output of the process has been forwarded.
+    fn join_output_threads(&mut self) -> TypemakeResult<()> {
+        for output_thread in self.output_threads.drain(..) {
+            output_thread
+                .join()
+                .expect("Output forwarding thread panicked")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the CPU time in seconds, including the reaped children, and the resident set size in bytes
+/// of all processes in the given process group, read from `/proc`.
+/// Returns `None` if `/proc` is not available.
+fn process_group_usage(process_group: u32) -> Option<(f64, u64)> {
+    // Safety: sysconf has no memory safety requirements.
+    let (clock_ticks, page_size) = unsafe {
+        (
+            libc::sysconf(libc::_SC_CLK_TCK),
+            libc::sysconf(libc::_SC_PAGESIZE),
+        )
+    };
+    if clock_ticks <= 0 || page_size <= 0 {
+        return None;
+    }
+
+    let mut cpu_ticks = 0;
+    let mut rss_pages = 0;
+    for entry in read_dir("/proc").ok()?.flatten() {
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .bytes()
+            .all(|byte| byte.is_ascii_digit())
+        {
+            continue;
+        }
+        // Processes may terminate while they are read.
+        let stat = match read_to_string(entry.path().join("stat")) {
+            Ok(stat) => stat,
+            Err(_) => continue,
+        };
+        // The command name may contain spaces and parentheses, so the fields are counted from its closing parenthesis,
+        // starting with the state as the third field of the stat file.
+        let fields: Vec<_> = match stat.rfind(')') {
+            Some(index) => stat[index + 1..].split_whitespace().collect(),
+            None => continue,
+        };
+        if fields.len() < 22 || fields[2].parse() != Ok(process_group) {
+            continue;
+        }
+        let field = |index: usize| fields[index].parse::<u64>().unwrap_or(0);
+        // utime, stime, cutime and cstime, followed by rss in pages.
+        cpu_ticks += field(11) + field(12) + field(13) + field(14);
+        rss_pages += field(21);
+    }
+    Some((
+        cpu_ticks as f64 / clock_ticks as f64,
+        rss_pages * page_size as u64,
+    ))
+}
+
+/// Converts a `timeval` into seconds.
+fn timeval_to_seconds(timeval: libc::timeval) -> f64 {
+    timeval.tv_sec as f64 + timeval.tv_usec as f64 / 1e6
+}
+
+/// Spawns a thread that copies the given output of a process into the log file and forwards it line-by-line to typemake's log.
+fn spawn_output_thread(
+    output: impl 'static + Read + Send,
+    instance_name: String,
+    log_file: Arc<Mutex<File>>,
+    is_stderr: bool,
+) -> JoinHandle<std::io::Result<()>> {
+    std::thread::spawn(move || {
+        let mut output = BufReader::new(output);
+        let mut line = Vec::new();
+        while output.read_until(b'\n', &mut line)? > 0 {
+            log_file.lock().unwrap().write_all(&line)?;
+
+            let line_string = String::from_utf8_lossy(&line);
+            let line_string = line_string.trim_end_matches(&['\n', '\r'][..]);
+            if is_stderr {
+                error!("[{}] {}", instance_name, line_string);
+            } else {
+                info!("[{}] {}", instance_name, line_string);
+            }
+            line.clear();
+        }
+        log_file.lock().unwrap().flush()
+    })
+}
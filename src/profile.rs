@@ -0,0 +1,177 @@
+//! Profiles, i.e. named sets of defaults for the command line arguments, defined in `typemake.toml`.
+//!
+//! Profiles are read from `typemake.toml` in the working directory and in the user configuration directory,
+//! i.e. `$XDG_CONFIG_HOME/typemake/typemake.toml` or `~/.config/typemake/typemake.toml`.
+//! A profile in the working directory takes precedence over a profile with the same name in the user configuration directory.
+//!
+//! ```toml
+//! [profiles.cluster]
+//! jobs = 100
+//! keep_going = true
+//! latency_wait = 5
+//! executor = "slurm"
+//! sbatch_arguments = "--partition=short"
+//! container_runtime = "apptainer"
+//! resources = { threads = 4, memory = 8000 }
+//! targets = ["results/summary.txt"]
+//! configfiles = ["cluster.yaml"]
+//! config = { "dataset.name" = "large" }
+//! ```
+//!
+//! Arguments given on the command line take precedence over the values of the profile,
+//! e.g. `--no-keep-going` disables `keep_going` of the profile.
+//! Targets of the profile are used only if no targets are given on the command line,
+//! while configuration files and overrides of the profile are applied before those given on the command line.
+
+use crate::cli::CliArguments;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use typemake::error::{TypemakeError, TypemakeResult};
+
+/// The name of the file defining the profiles.
+const PROFILE_FILE_NAME: &str = "typemake.toml";
+/// The name of the profile that is used if no profile is given on the command line.
+const DEFAULT_PROFILE_NAME: &str = "default";
+
+/// The contents of a profile file.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    /// The profiles defined in the file, by name.
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// A named set of defaults for the command line arguments.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// The maximum number of tool instances executed in parallel.
+    pub jobs: Option<usize>,
+    /// The time in seconds to wait for outputs of tool instances to appear.
+    pub latency_wait: Option<f64>,
+    /// If true, a failing tool instance does not abort the execution of independent tool instances.
+    pub keep_going: Option<bool>,
+    /// The executor running the tool instances.
+    pub executor: Option<String>,
+    /// The command submitting a job script to the batch system.
+    pub submit_command: Option<String>,
+    /// The command querying the status of a job.
+    pub status_command: Option<String>,
+    /// The command cancelling a job.
+    pub cancel_command: Option<String>,
+    /// Additional arguments passed to `sbatch`.
+    pub sbatch_arguments: Option<String>,
+    /// The runtime executing tool instances with a container image.
+    pub container_runtime: Option<String>,
+    /// The resources requested for tool instances that do not declare them.
+    #[serde(default)]
+    pub resources: BTreeMap<String, Value>,
+    /// The artifacts to produce if no targets are given on the command line.
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// Configuration files, merged before those given on the command line.
+    #[serde(default)]
+    pub configfiles: Vec<PathBuf>,
+    /// Configuration overrides, applied before those given on the command line.
+    #[serde(default)]
+    pub config: BTreeMap<String, Value>,
+}
+
+impl Profile {
+    /// Applies the values of this profile to the given command line arguments, where they were not given explicitly.
+    pub fn apply(&self, cli_arguments: &mut CliArguments) {
+        cli_arguments.jobs = cli_arguments.jobs.or(self.jobs);
+        cli_arguments.latency_wait = cli_arguments.latency_wait.or(self.latency_wait);
+        if !cli_arguments.keep_going && !cli_arguments.no_keep_going {
+            cli_arguments.keep_going = self.keep_going.unwrap_or(false);
+        }
+        for (argument, value) in [
+            (&mut cli_arguments.executor, &self.executor),
+            (&mut cli_arguments.submit_command, &self.submit_command),
+            (&mut cli_arguments.status_command, &self.status_command),
+            (&mut cli_arguments.cancel_command, &self.cancel_command),
+            (&mut cli_arguments.sbatch_arguments, &self.sbatch_arguments),
+            (
+                &mut cli_arguments.container_runtime,
+                &self.container_runtime,
+            ),
+        ] {
+            if argument.is_none() {
+                *argument = value.clone();
+            }
+        }
+
+        // Values given as arguments are inserted after those of the profile, such that they take precedence.
+        cli_arguments.resources = override_arguments(&self.resources)
+            .chain(cli_arguments.resources.drain(..))
+            .collect();
+        cli_arguments.config = override_arguments(&self.config)
+            .chain(cli_arguments.config.drain(..))
+            .collect();
+        cli_arguments.configfile = self
+            .configfiles
+            .iter()
+            .cloned()
+            .chain(cli_arguments.configfile.drain(..))
+            .collect();
+
+        if cli_arguments.targets.iter().all(|target| target.is_empty()) {
+            cli_arguments.targets = self.targets.clone();
+        }
+    }
+}
+
+/// Converts the given values into arguments of the form `key=value`.
+/// Values are written as JSON, which is parsed as the same YAML value.
+fn override_arguments(values: &BTreeMap<String, Value>) -> impl '_ + Iterator<Item = String> {
+    values
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+}
+
+/// Returns the paths of the profile files, in order of decreasing precedence.
+fn profile_files() -> Vec<PathBuf> {
+    let mut profile_files = vec![PathBuf::from(PROFILE_FILE_NAME)];
+    let user_config_directory = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    if let Some(user_config_directory) = user_config_directory {
+        profile_files.push(
+            user_config_directory
+                .join("typemake")
+                .join(PROFILE_FILE_NAME),
+        );
+    }
+    profile_files
+}
+
+/// Loads the profile with the given name, or the default profile if no name is given.
+/// Returns an error if a profile with the given name does not exist, and `None` if no name is given and the default profile does not exist.
+pub fn load_profile(name: Option<&str>) -> TypemakeResult<Option<Profile>> {
+    let mut known_profiles = Vec::new();
+    for profile_file in profile_files() {
+        if !profile_file.exists() {
+            continue;
+        }
+        let content = std::fs::read_to_string(&profile_file)?;
+        let mut profiles = toml::from_str::<ProfileFile>(&content)
+            .map_err(|error| TypemakeError::ConfigError(format!("{:?}: {}", profile_file, error)))?
+            .profiles;
+        if let Some(profile) = profiles.remove(name.unwrap_or(DEFAULT_PROFILE_NAME)) {
+            return Ok(Some(profile));
+        }
+        known_profiles.extend(profiles.into_keys());
+    }
+
+    match name {
+        Some(name) => Err(TypemakeError::ConfigError(format!(
+            "unknown profile {:?}, known profiles are {:?}",
+            name, known_profiles
+        ))),
+        None => Ok(None),
+    }
+}
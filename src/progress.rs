@@ -0,0 +1,158 @@
+//! A live display of the progress of a workflow for interactive terminals.
+//!
+//! The display is a block of lines drawn on stderr below the log.
+//! Log messages are written through the `ProgressLogger`, which removes the block before each message and draws it again afterwards,
+//! such that the log scrolls above the display.
+
+use lazy_static::lazy_static;
+use log::{Log, Metadata, Record};
+use std::io::Write;
+use std::sync::Mutex;
+
+/// The width of the progress bar in characters.
+const PROGRESS_BAR_WIDTH: usize = 30;
+/// The terminal width assumed if it cannot be determined.
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+lazy_static! {
+    /// The lines of the display, or `None` if no display is shown.
+    static ref DISPLAY: Mutex<Option<DisplayState>> = Mutex::new(None);
+}
+
+/// The state of the display.
+#[derive(Debug, Default)]
+struct DisplayState {
+    /// The lines of the display.
+    lines: Vec<String>,
+    /// The number of lines currently drawn on the terminal.
+    drawn_lines: usize,
+}
+
+impl DisplayState {
+    /// Removes the drawn lines from the terminal.
+    fn clear(&mut self, stderr: &mut impl Write) {
+        if self.drawn_lines > 0 {
+            // Move the cursor to the start of the first drawn line and clear everything below.
+            let _ = write!(stderr, "\r\x1b[{}A\x1b[J", self.drawn_lines);
+            self.drawn_lines = 0;
+        }
+    }
+
+    /// Draws the lines on the terminal, truncated to its width.
+    fn draw(&mut self, stderr: &mut impl Write) {
+        let width = terminal_width();
+        for line in &self.lines {
+            let line: String = line.chars().take(width.saturating_sub(1)).collect();
+            let _ = writeln!(stderr, "{}", line);
+        }
+        self.drawn_lines = self.lines.len();
+        let _ = stderr.flush();
+    }
+}
+
+/// A handle to the live display, which removes the display when dropped.
+#[derive(Debug)]
+pub struct ProgressDisplay {
+    /// Prevents construction outside of `start`.
+    _private: (),
+}
+
+impl ProgressDisplay {
+    /// Starts showing the live display.
+    pub fn start() -> Self {
+        *DISPLAY.lock().unwrap() = Some(DisplayState::default());
+        Self { _private: () }
+    }
+
+    /// Replaces the lines of the display.
+    pub fn update(&self, lines: Vec<String>) {
+        let mut display = DISPLAY.lock().unwrap();
+        if let Some(display) = display.as_mut() {
+            let mut stderr = std::io::stderr();
+            display.clear(&mut stderr);
+            display.lines = lines;
+            display.draw(&mut stderr);
+        }
+    }
+}
+
+impl Drop for ProgressDisplay {
+    fn drop(&mut self) {
+        if let Some(mut display) = DISPLAY.lock().unwrap().take() {
+            let mut stderr = std::io::stderr();
+            display.clear(&mut stderr);
+            let _ = stderr.flush();
+        }
+    }
+}
+
+/// A logger that keeps the live display below the messages written by the wrapped logger.
+pub struct ProgressLogger {
+    /// The wrapped logger.
+    inner: Box<dyn Log>,
+}
+
+impl ProgressLogger {
+    /// Wraps the given logger.
+    pub fn new(inner: Box<dyn Log>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Log for ProgressLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let mut display = DISPLAY.lock().unwrap();
+        if let Some(display) = display.as_mut() {
+            if !self.inner.enabled(record.metadata()) {
+                return;
+            }
+            let mut stderr = std::io::stderr();
+            display.clear(&mut stderr);
+            self.inner.log(record);
+            self.inner.flush();
+            display.draw(&mut stderr);
+        } else {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Returns a progress bar showing the given fraction of done work, e.g. `[=====>    ]`.
+pub fn progress_bar(done: usize, total: usize) -> String {
+    let filled = (done.min(total) * PROGRESS_BAR_WIDTH)
+        .checked_div(total)
+        .unwrap_or(PROGRESS_BAR_WIDTH);
+    let mut bar = "=".repeat(filled);
+    if filled < PROGRESS_BAR_WIDTH {
+        bar.push('>');
+        bar.push_str(&" ".repeat(PROGRESS_BAR_WIDTH - filled - 1));
+    }
+    format!("[{}]", bar)
+}
+
+/// Returns true if stderr is connected to a terminal.
+pub fn stderr_is_terminal() -> bool {
+    // Safety: isatty has no memory safety requirements.
+    unsafe { libc::isatty(libc::STDERR_FILENO) == 1 }
+}
+
+/// Returns the width of the terminal connected to stderr.
+fn terminal_width() -> usize {
+    // Safety: the ioctl writes only into the given struct.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) } == 0
+        && size.ws_col > 0
+    {
+        size.ws_col as usize
+    } else {
+        DEFAULT_TERMINAL_WIDTH
+    }
+}
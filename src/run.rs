@@ -0,0 +1,145 @@
+//! Loading and executing workflows programmatically.
+
+use crate::config::{config_value, load_config};
+use crate::error::TypemakeResult;
+use crate::events::{Event, EventSink};
+use crate::executor::Executor;
+use crate::interpreter::{Interpreter, SelectedInterpreter};
+use crate::parser::{parse_typefile, Typefile};
+use crate::scheduler::{Scheduler, SchedulerOptions};
+use crate::state::StateDirectory;
+use log::info;
+use std::path::{Path, PathBuf};
+
+/// Options describing which workflow to execute and how.
+pub struct WorkflowOptions {
+    /// The path to the root typefile.
+    pub typefile: PathBuf,
+    /// The directory in which typemake stores its state, like metadata, logs and locks.
+    pub state_directory: PathBuf,
+    /// The artifacts to produce. If empty, all tool instances are executed.
+    pub targets: Vec<String>,
+    /// Configuration files in YAML, TOML or JSON format, whose values are available to the typefile in the dict `config`.
+    /// Later files override values of earlier ones.
+    pub config_files: Vec<PathBuf>,
+    /// Configuration overrides of the form `key=value`, applied after the configuration files.
+    pub config_overrides: Vec<String>,
+    /// The options controlling the execution of the tool instances.
+    pub scheduler: SchedulerOptions,
+    /// The destination of the execution events, if any.
+    pub events: Option<EventSink>,
+    /// The executor running the scripts of the tool instances.
+    /// If `None`, they are executed as local processes.
+    pub executor: Option<Box<dyn Executor>>,
+}
+
+impl Default for WorkflowOptions {
+    fn default() -> Self {
+        Self {
+            typefile: PathBuf::from("Typefile"),
+            state_directory: PathBuf::from(".typemake"),
+            targets: Vec::new(),
+            config_files: Vec::new(),
+            config_overrides: Vec::new(),
+            scheduler: SchedulerOptions {
+                jobs: 1,
+                ..Default::default()
+            },
+            events: None,
+            executor: None,
+        }
+    }
+}
+
+/// Parses the typefile and executes its toplevel scripts in a new interpreter, with the configuration defined as `config`.
+/// Since interpreters might be limited to a single instance, this fails if an interpreter was created before in the same process.
+pub fn load_workflow(
+    typefile: &Path,
+    config_files: &[PathBuf],
+    config_overrides: &[String],
+) -> TypemakeResult<(Typefile, SelectedInterpreter)> {
+    // Parse typefile
+    info!("Parsing typefile '{:?}'", typefile);
+    let workflow = parse_typefile(typefile)?;
+
+    info!("Creating interpreter");
+    let mut interpreter = SelectedInterpreter::new()?;
+    info!("Interpreter version is {}", interpreter.version()?);
+
+    info!("Loading configuration");
+    let config = load_config(config_files, config_overrides)?;
+    interpreter.define("config", &config_value(&config))?;
+
+    info!("Executing toplevel scripts");
+    interpreter.run(&workflow.code_lines)?;
+    Ok((workflow, interpreter))
+}
+
+/// Executes the workflow defined by the typefile to produce the targets given in the options.
+/// Returns an error if any target could not be produced.
+/// It creates an interpreter with [`load_workflow`], so it can only be called once per process, and not after [`load_workflow`].
+pub fn run_workflow(options: WorkflowOptions) -> TypemakeResult<()> {
+    if let Some(events) = &options.events {
+        events.emit(Event::WorkflowStarted {
+            typefile: options.typefile.clone(),
+            targets: options.targets.clone(),
+            pid: std::process::id(),
+        });
+    }
+
+    let events = options.events.clone();
+    let result = execute_workflow(options);
+    if let Some(events) = &events {
+        events.emit(Event::WorkflowFinished {
+            success: result.is_ok(),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+    }
+    result
+}
+
+/// Executes the workflow, without emitting the events describing the start and the end of the workflow.
+fn execute_workflow(options: WorkflowOptions) -> TypemakeResult<()> {
+    let (workflow, mut interpreter) = load_workflow(
+        &options.typefile,
+        &options.config_files,
+        &options.config_overrides,
+    )?;
+
+    info!("Building workflow DAG");
+    let mut scheduler = Scheduler::new(
+        workflow.tools,
+        workflow.types,
+        &mut interpreter,
+        options.targets,
+        options.scheduler,
+        StateDirectory::new(options.state_directory),
+    )?;
+    info!(
+        "Workflow DAG has {} tool instances",
+        scheduler.graph().len()
+    );
+    if let Some(events) = options.events {
+        events.emit(Event::DagBuilt {
+            tool_instances: scheduler.graph().len(),
+            uninstantiated_tools: scheduler
+                .uninstantiated_tools()
+                .into_iter()
+                .map(|(name, _)| name.to_owned())
+                .collect(),
+        });
+        scheduler.set_event_sink(events);
+    }
+    if let Some(executor) = options.executor {
+        scheduler.set_executor(executor);
+    }
+
+    info!("Locking outputs");
+    scheduler.acquire_lock()?;
+
+    info!("Executing workflow");
+    scheduler.run()?;
+
+    info!("Terminating");
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! The summary of failed tool instances printed at the end of a workflow executed with `--keep-going`.
+
+use std::os::unix::process::ExitStatusExt;
+use std::process::ExitStatus;
+
+/// The number of lines shown from the end of the log of each failed tool instance.
+pub const FAILURE_LOG_LINES: usize = 10;
+
+/// A tool instance that failed after all its attempts.
+#[derive(Debug, Clone)]
+pub struct InstanceFailure {
+    /// The name of the failed instance.
+    pub name: String,
+    /// A description of the failure.
+    pub reason: String,
+    /// The exit status of the last attempt, or `None` if the instance has no script and thus no process.
+    pub exit_status: Option<ExitStatus>,
+    /// The last lines of the log of the instance.
+    pub log_tail: Vec<String>,
+    /// The names of the instances that were not executed because they depend on the failed instance.
+    pub skipped_dependants: Vec<String>,
+}
+
+impl InstanceFailure {
+    /// Returns the exit code of the last attempt, or the signal that terminated it.
+    /// Instances without a process have no exit code.
+    pub fn exit_code(&self) -> String {
+        let exit_status = match self.exit_status {
+            Some(exit_status) => exit_status,
+            None => return "-".to_owned(),
+        };
+        if let Some(code) = exit_status.code() {
+            code.to_string()
+        } else if let Some(signal) = exit_status.signal() {
+            format!("signal {}", signal)
+        } else {
+            "-".to_owned()
+        }
+    }
+}
+
+/// Prints a table of the given failed instances, each followed by the last lines of its log and its skipped dependants.
+pub fn print_failure_summary(failures: &[InstanceFailure]) {
+    println!();
+    println!("{:<30} {:>9}  reason", "failed tool instance", "exit code");
+    for failure in failures {
+        println!(
+            "{:<30} {:>9}  {}",
+            failure.name,
+            failure.exit_code(),
+            failure.reason
+        );
+        if !failure.log_tail.is_empty() {
+            println!("    last lines of log:");
+            for line in &failure.log_tail {
+                println!("    | {}", line);
+            }
+        }
+        if !failure.skipped_dependants.is_empty() {
+            println!(
+                "    skipped dependants: {}",
+                failure.skipped_dependants.join(", ")
+            );
+        }
+    }
+    println!();
+}
@@ -0,0 +1,1467 @@
+//! The scheduler deciding which tool instances to execute and when.
+
+use crate::container::ContainerRuntime;
+use crate::error::{TypemakeError, TypemakeResult};
+use crate::events::{Event, EventSink};
+use crate::executor::{Executor, Job, LocalExecutor};
+use crate::interpreter::{Interpreter, InterpreterValue};
+use crate::interrupt::{install_interrupt_handlers, received_signal};
+use crate::process::ProcessTermination;
+use crate::progress::{progress_bar, ProgressDisplay};
+use crate::scheduler::failures::{print_failure_summary, InstanceFailure, FAILURE_LOG_LINES};
+use crate::state::lock::{locked_artifacts, WorkflowLock};
+use crate::state::metadata::Benchmark;
+use crate::state::StateDirectory;
+use crate::workflow::{ArtifactType, Resources, Tool, ToolInstance, WorkflowGraph};
+use log::{debug, info, warn};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{create_dir_all, read_dir, remove_dir_all, remove_file, set_permissions};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::{Duration, Instant, SystemTime};
+use traitgraph::index::GraphIndex;
+
+pub mod failures;
+
+/// The interval in which running tool instances are polled for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// The time running tool instances are given to terminate after forwarding an interrupt signal to them, before they are killed.
+const INTERRUPT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// The interval in which the live progress display is updated.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The interval in which the outputs of a finished tool instance are checked while waiting for filesystem latency.
+const LATENCY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The node index type of the workflow graph used by the scheduler.
+type NodeIndex = traitgraph::index::NodeIndex<usize>;
+
+/// The execution state of a tool instance.
+#[derive(Debug)]
+enum ToolInstanceState {
+    /// The instance has not been started yet.
+    Pending,
+    /// The instance is currently executing.
+    Running(Box<dyn Job>),
+    /// The instance failed and waits until it may be retried.
+    Backoff {
+        /// The instant after which the instance may be retried.
+        until: Instant,
+    },
+    /// The instance failed after all its attempts, and its dependants are not executed.
+    /// Only used if the scheduler keeps going after failures.
+    Failed {
+        /// A description of the failure.
+        reason: String,
+        /// The exit status of the last attempt, or `None` if the instance has no script and thus no process.
+        exit_status: Option<ExitStatus>,
+    },
+    /// The instance has finished.
+    /// `executed` is false if the instance was skipped because its outputs were up to date.
+    Done {
+        /// True if the instance was actually executed.
+        executed: bool,
+    },
+}
+
+/// Options controlling how the scheduler executes a workflow.
+#[derive(Debug, Clone, Default)]
+pub struct SchedulerOptions {
+    /// The maximum number of tool instances running in parallel.
+    pub jobs: usize,
+    /// The time to wait for the outputs of a finished tool instance to appear.
+    pub latency_wait: Duration,
+    /// If set, a failing instance only prevents the execution of its dependants, and the failures are summarised after all other instances finished.
+    pub keep_going: bool,
+    /// The resources requested for instances that do not declare them.
+    pub default_resources: Resources,
+    /// If set, a live display of the progress and the running instances is shown on stderr.
+    pub progress: bool,
+    /// If set, the instances producing the targets are executed regardless of their outputs, or all instances if there are no targets.
+    /// Additionally, protected outputs may be overwritten.
+    pub force: bool,
+    /// The tools whose instances are executed regardless of their outputs.
+    /// Since their dependants are outdated then, they are executed as well.
+    pub forcerun: Vec<String>,
+    /// If not empty, only the instances of these tools and their predecessors are executed.
+    pub until: Vec<String>,
+    /// The runtime executing the scripts of instances with a container image.
+    pub container_runtime: ContainerRuntime,
+}
+
+/// The status of a tool instance, describing if it needs to be executed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstanceStatus {
+    /// The outputs of the instance are up to date.
+    Done,
+    /// The outputs of the instance are outdated, for the given reason.
+    Stale(String),
+    /// The instance was never executed.
+    Pending,
+    /// The last execution of the instance failed.
+    Failed,
+    /// The instance is currently executed by another typemake process.
+    Running,
+}
+
+/// The scheduler of a workflow.
+/// It owns the workflow graph and extends it whenever a checkpoint tool instance finishes.
+pub struct Scheduler<'interpreter, InterpreterType: Interpreter> {
+    /// The tools of the typefile.
+    tools: BTreeMap<String, Tool>,
+    /// The artifact types of the typefile.
+    types: BTreeMap<String, ArtifactType>,
+    /// The interpreter used to evaluate tool properties.
+    interpreter: &'interpreter mut InterpreterType,
+    /// The tool instances known to the scheduler.
+    graph: WorkflowGraph,
+    /// The execution states of the tool instances, indexed by their nodes.
+    states: Vec<ToolInstanceState>,
+    /// The number of started attempts of executing the tool instances, indexed by their nodes.
+    attempts: Vec<u64>,
+    /// The artifacts requested by the user. If empty, all tool instances are executed.
+    targets: Vec<String>,
+    /// The options controlling the execution.
+    options: SchedulerOptions,
+    /// The state directory of typemake.
+    state_directory: StateDirectory,
+    /// The lock on the outputs of the required tool instances, if acquired.
+    lock: Option<WorkflowLock>,
+    /// The destination of the execution events, if any.
+    events: Option<EventSink>,
+    /// The executor running the scripts of the tool instances.
+    executor: Box<dyn Executor>,
+}
+
+impl<'interpreter, InterpreterType: Interpreter> Scheduler<'interpreter, InterpreterType> {
+    /// Creates a new scheduler, evaluating the given tools and building the workflow graph from them.
+    /// Returns an error if the declared types of the artifacts in the workflow graph do not match.
+    pub fn new(
+        tools: BTreeMap<String, Tool>,
+        types: BTreeMap<String, ArtifactType>,
+        interpreter: &'interpreter mut InterpreterType,
+        targets: Vec<String>,
+        mut options: SchedulerOptions,
+        state_directory: StateDirectory,
+    ) -> TypemakeResult<Self> {
+        options.jobs = options.jobs.max(1);
+        for (option, tool_names) in [
+            ("--forcerun", &options.forcerun),
+            ("--until", &options.until),
+        ] {
+            if let Some(unknown) = tool_names.iter().find(|name| !tools.contains_key(*name)) {
+                return Err(TypemakeError::GeneralError(format!(
+                    "Unknown tool {:?} given to {}",
+                    unknown, option
+                )));
+            }
+        }
+        let mut scheduler = Self {
+            tools,
+            types,
+            interpreter,
+            graph: Default::default(),
+            states: Vec::new(),
+            attempts: Vec::new(),
+            targets,
+            options,
+            state_directory,
+            lock: None,
+            events: None,
+            executor: Box::new(LocalExecutor),
+        };
+        scheduler.evaluate_tools()?;
+        Ok(scheduler)
+    }
+
+    /// Returns the workflow graph built by the scheduler.
+    pub fn graph(&self) -> &WorkflowGraph {
+        &self.graph
+    }
+
+    /// Returns the names of the tools that could not be instantiated yet, together with their evaluation errors.
+    pub fn uninstantiated_tools(&self) -> Vec<(&str, Vec<String>)> {
+        self.tools
+            .values()
+            .filter(|tool| self.graph.instance_node(&tool.name).is_none())
+            .map(|tool| (tool.name.as_str(), tool.evaluation_errors()))
+            .collect()
+    }
+
+    /// Returns the status of each tool instance required to produce the targets, without executing anything.
+    /// Instances whose predecessors are not done are stale.
+    pub fn instance_statuses(&self) -> TypemakeResult<Vec<(&ToolInstance, InstanceStatus)>> {
+        let required = self.required_nodes();
+        let running_artifacts = locked_artifacts(&self.state_directory.lock_directory())?;
+        let metadata_store = self.state_directory.metadata_store();
+        let mut statuses: Vec<Option<InstanceStatus>> = vec![None; self.graph.len()];
+        let mut remaining: Vec<_> = self
+            .graph
+            .nodes()
+            .into_iter()
+            .filter(|node| required[node.as_usize()])
+            .collect();
+
+        // Compute the statuses in topological order, since they depend on the statuses of the predecessors.
+        while !remaining.is_empty() {
+            let remaining_count = remaining.len();
+            let mut index = 0;
+            while index < remaining.len() {
+                let node = remaining[index];
+                if self
+                    .graph
+                    .predecessors(node)
+                    .into_iter()
+                    .any(|predecessor| statuses[predecessor.as_usize()].is_none())
+                {
+                    index += 1;
+                    continue;
+                }
+
+                let tool_instance = self.graph.tool_instance(node);
+                let metadata = metadata_store.load(&tool_instance.name)?;
+                let status = if metadata.incomplete
+                    && tool_instance
+                        .outputs
+                        .iter()
+                        .any(|output| running_artifacts.contains(output))
+                {
+                    InstanceStatus::Running
+                } else if metadata.incomplete
+                    && metadata
+                        .benchmarks
+                        .last()
+                        .is_some_and(|benchmark| !benchmark.success)
+                {
+                    InstanceStatus::Failed
+                } else {
+                    match self.outdated_reason(node, |predecessor| {
+                        statuses[predecessor.as_usize()] != Some(InstanceStatus::Done)
+                    })? {
+                        None => InstanceStatus::Done,
+                        Some(_) if metadata.benchmarks.is_empty() => InstanceStatus::Pending,
+                        Some(reason) => InstanceStatus::Stale(reason),
+                    }
+                };
+                statuses[node.as_usize()] = Some(status);
+                remaining.swap_remove(index);
+            }
+
+            if remaining.len() == remaining_count {
+                // The remaining instances depend on each other cyclically, so they can never be executed.
+                for node in remaining.drain(..) {
+                    statuses[node.as_usize()] = Some(InstanceStatus::Pending);
+                }
+            }
+        }
+
+        Ok(self
+            .graph
+            .nodes()
+            .into_iter()
+            .filter_map(|node| {
+                let status = statuses[node.as_usize()].take()?;
+                Some((self.graph.tool_instance(node), status))
+            })
+            .collect())
+    }
+
+    /// Sends events about the execution of tool instances to the given sink.
+    pub fn set_event_sink(&mut self, events: EventSink) {
+        self.events = Some(events);
+    }
+
+    /// Runs the scripts of the tool instances with the given executor instead of as local processes.
+    pub fn set_executor(&mut self, executor: Box<dyn Executor>) {
+        self.executor = executor;
+    }
+
+    /// Emits the given event, if the scheduler has an event sink.
+    fn emit(&self, event: Event) {
+        if let Some(events) = &self.events {
+            events.emit(event);
+        }
+    }
+
+    /// Locks the outputs of all tool instances required to produce the targets,
+    /// such that no other typemake process can produce them concurrently.
+    /// The lock is extended whenever the workflow graph is extended, and released when the scheduler is dropped.
+    pub fn acquire_lock(&mut self) -> TypemakeResult<()> {
+        self.lock = Some(WorkflowLock::acquire(
+            self.state_directory.lock_directory(),
+            self.planned_outputs(),
+        )?);
+        Ok(())
+    }
+
+    /// Returns the outputs of all tool instances required to produce the targets.
+    fn planned_outputs(&self) -> BTreeSet<String> {
+        let required = self.required_nodes();
+        self.graph
+            .nodes()
+            .into_iter()
+            .filter(|node| required[node.as_usize()])
+            .flat_map(|node| self.graph.tool_instance(node).outputs.iter().cloned())
+            .collect()
+    }
+
+    /// Evaluates the properties of all tools that have not been instantiated yet,
+    /// and adds instances of all tools that are fully evaluated to the workflow graph.
+    /// Afterwards, the declared types of the artifacts in the workflow graph are checked.
+    /// If a lock was acquired, it is extended to the outputs of the new instances.
+    fn evaluate_tools(&mut self) -> TypemakeResult<()> {
+        let mut instance_count = 0;
+        for tool in self.tools.values_mut() {
+            if self.graph.instance_node(&tool.name).is_some() {
+                continue;
+            }
+
+            tool.evaluate(self.interpreter);
+            if let Some(mut tool_instance) = tool.instantiate() {
+                debug!("Instantiated tool {:?}", tool.name);
+                let resources = &mut tool_instance.resources;
+                let default_resources = &self.options.default_resources;
+                resources.threads = resources.threads.or(default_resources.threads);
+                resources.memory = resources.memory.or(default_resources.memory);
+                resources.timeout = resources.timeout.or(default_resources.timeout);
+                self.graph.add_tool_instance(tool_instance)?;
+                self.states.push(ToolInstanceState::Pending);
+                self.attempts.push(0);
+                instance_count += 1;
+            } else {
+                debug!(
+                    "Could not instantiate tool {:?}: {}",
+                    tool.name,
+                    tool.evaluation_errors().join("; ")
+                );
+            }
+        }
+
+        info!(
+            "Added {} tool instances to the workflow DAG",
+            instance_count
+        );
+
+        let type_errors = self.graph.type_errors(&self.types);
+        if !type_errors.is_empty() {
+            return Err(TypemakeError::GeneralError(type_errors.join("\n")));
+        }
+
+        if self.lock.is_some() {
+            let planned_outputs = self.planned_outputs();
+            if let Some(lock) = &mut self.lock {
+                lock.extend(planned_outputs)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes the workflow until all targets are produced.
+    /// If the scheduler keeps going after failures, a summary of the failed instances is printed after all other instances finished.
+    /// If typemake receives SIGINT or SIGTERM, the signal is forwarded to all running instances and an error is returned once they terminated.
+    pub fn run(&mut self) -> TypemakeResult<()> {
+        install_interrupt_handlers()?;
+        let display = if self.options.progress {
+            Some(ProgressDisplay::start())
+        } else {
+            None
+        };
+        let mut last_display_update: Option<Instant> = None;
+        loop {
+            if let Some(signal) = received_signal() {
+                return self.interrupt(signal);
+            }
+
+            if let Some(display) = &display {
+                if last_display_update.is_none_or(|instant| instant.elapsed() >= PROGRESS_INTERVAL)
+                {
+                    display.update(self.progress_lines());
+                    last_display_update = Some(Instant::now());
+                }
+            }
+
+            let required = self.required_nodes();
+            let ready_node = self
+                .graph
+                .nodes()
+                .into_iter()
+                .find(|&node| required[node.as_usize()] && self.is_ready(node));
+            let running_count = self
+                .states
+                .iter()
+                .filter(|state| matches!(state, ToolInstanceState::Running(_)))
+                .count();
+
+            if running_count < self.options.jobs {
+                if let Some(node) = ready_node {
+                    self.start(node)?;
+                    continue;
+                }
+            }
+
+            if running_count == 0
+                && !self
+                    .states
+                    .iter()
+                    .any(|state| matches!(state, ToolInstanceState::Backoff { .. }))
+            {
+                break;
+            }
+
+            if !self.poll()? {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+
+        drop(display);
+        let failures = self.failures()?;
+        if failures.is_empty() {
+            return self.check_completion();
+        }
+
+        print_failure_summary(&failures);
+        let mut errors = vec![format!(
+            "Failed tool instances: {}",
+            failures
+                .iter()
+                .map(|failure| format!("{:?}", failure.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )];
+        if let Err(error) = self.check_completion() {
+            errors.push(error.to_string());
+        }
+        Err(TypemakeError::GeneralError(errors.join("\n")))
+    }
+
+    /// Returns the number of done instances and the number of all instances required to produce the targets.
+    fn progress(&self) -> (usize, usize) {
+        let required = self.required_nodes();
+        let total = required.iter().filter(|&&required| required).count();
+        let done = self
+            .graph
+            .nodes()
+            .into_iter()
+            .filter(|node| {
+                required[node.as_usize()]
+                    && matches!(self.states[node.as_usize()], ToolInstanceState::Done { .. })
+            })
+            .count();
+        (done, total)
+    }
+
+    /// Returns the lines of the live progress display, i.e. a progress bar followed by the running instances
+    /// with their elapsed time, their requested resources and, if it can be measured, their current resource usage.
+    fn progress_lines(&self) -> Vec<String> {
+        let (done, total) = self.progress();
+        let mut running = Vec::new();
+        let mut failed = 0;
+        for node in self.graph.nodes() {
+            match &self.states[node.as_usize()] {
+                ToolInstanceState::Running(job) => running.push((node, job)),
+                ToolInstanceState::Failed { .. } => failed += 1,
+                _ => {}
+            }
+        }
+
+        let mut status = format!(
+            "{} {} of {} tool instances done, {} running",
+            progress_bar(done, total),
+            done,
+            total,
+            running.len()
+        );
+        if failed > 0 {
+            status.push_str(&format!(", {} failed", failed));
+        }
+
+        let mut lines = vec![status];
+        for (node, job) in running {
+            let tool_instance = self.graph.tool_instance(node);
+            let attempt = self.attempts[node.as_usize()];
+            let resources = tool_instance.resources_for_attempt(attempt);
+            let mut line = format!(
+                "  {:<30} {:>8.1}s",
+                tool_instance.name,
+                job.elapsed().as_secs_f64()
+            );
+            if attempt > 1 {
+                line.push_str(&format!("  attempt {}", attempt));
+            }
+            // The measured usage is shown relative to the requested resources, e.g. "cpu 1.8 of 2  mem 310 of 1000 MB".
+            if let Some(usage) = job.resource_usage() {
+                line.push_str(&format!("  cpu {:.1}", usage.cpu_cores));
+                if let Some(threads) = resources.threads {
+                    line.push_str(&format!(" of {}", threads));
+                }
+                line.push_str(&format!("  mem {}", usage.rss / (1 << 20)));
+                if let Some(memory) = resources.memory {
+                    line.push_str(&format!(" of {}", memory));
+                }
+                line.push_str(" MB");
+            } else {
+                let mut requested = Vec::new();
+                if let Some(threads) = resources.threads {
+                    requested.push(format!("{} threads", threads));
+                }
+                if let Some(memory) = resources.memory {
+                    requested.push(format!("{} MB", memory));
+                }
+                if !requested.is_empty() {
+                    line.push_str(&format!("  requested {}", requested.join(", ")));
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Returns the instances that failed after all their attempts, together with the last lines of their logs and their skipped dependants.
+    fn failures(&self) -> TypemakeResult<Vec<InstanceFailure>> {
+        let required = self.required_nodes();
+        let mut failures = Vec::new();
+        for node in self.graph.nodes() {
+            let (reason, exit_status) = if let ToolInstanceState::Failed {
+                reason,
+                exit_status,
+            } = &self.states[node.as_usize()]
+            {
+                (reason.clone(), *exit_status)
+            } else {
+                continue;
+            };
+
+            let name = self.graph.tool_instance(node).name.clone();
+            let log_tail = match std::fs::read_to_string(self.state_directory.log_path(&name)) {
+                Ok(log) => {
+                    let lines: Vec<_> = log.lines().map(str::to_owned).collect();
+                    lines[lines.len().saturating_sub(FAILURE_LOG_LINES)..].to_vec()
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(error) => return Err(error.into()),
+            };
+            let skipped_dependants = self
+                .with_successors(self.graph.successors(node))
+                .into_iter()
+                .zip(self.graph.nodes())
+                .filter(|&(is_dependant, dependant)| {
+                    is_dependant
+                        && required[dependant.as_usize()]
+                        && matches!(
+                            self.states[dependant.as_usize()],
+                            ToolInstanceState::Pending
+                        )
+                })
+                .map(|(_, dependant)| self.graph.tool_instance(dependant).name.clone())
+                .collect();
+            failures.push(InstanceFailure {
+                name,
+                reason,
+                exit_status,
+                log_tail,
+                skipped_dependants,
+            });
+        }
+        Ok(failures)
+    }
+
+    /// Returns for each node if its instance is required to produce the targets.
+    fn required_nodes(&self) -> Vec<bool> {
+        let mut required = if self.targets.is_empty() {
+            vec![true; self.graph.len()]
+        } else {
+            self.with_predecessors(
+                self.targets
+                    .iter()
+                    .filter_map(|target| self.graph.producer(target))
+                    .collect(),
+            )
+        };
+
+        if !self.options.until.is_empty() {
+            let until = self.with_predecessors(
+                self.options
+                    .until
+                    .iter()
+                    .filter_map(|name| self.graph.instance_node(name))
+                    .collect(),
+            );
+            for (required, until) in required.iter_mut().zip(until) {
+                *required &= until;
+            }
+        }
+        required
+    }
+
+    /// Returns for each node if it is one of the given nodes or a transitive predecessor of them.
+    fn with_predecessors(&self, mut stack: Vec<NodeIndex>) -> Vec<bool> {
+        let mut result = vec![false; self.graph.len()];
+        while let Some(node) = stack.pop() {
+            if !result[node.as_usize()] {
+                result[node.as_usize()] = true;
+                stack.extend(self.graph.predecessors(node));
+            }
+        }
+        result
+    }
+
+    /// Returns for each node if it is one of the given nodes or a transitive successor of them.
+    fn with_successors(&self, mut stack: Vec<NodeIndex>) -> Vec<bool> {
+        let mut result = vec![false; self.graph.len()];
+        while let Some(node) = stack.pop() {
+            if !result[node.as_usize()] {
+                result[node.as_usize()] = true;
+                stack.extend(self.graph.successors(node));
+            }
+        }
+        result
+    }
+
+    /// Returns true if the instance at the given node is executed regardless of its outputs.
+    fn is_forced(&self, node: NodeIndex) -> bool {
+        let tool_instance = self.graph.tool_instance(node);
+        let is_target = self.targets.is_empty()
+            || tool_instance
+                .outputs
+                .iter()
+                .any(|output| self.targets.contains(output));
+        (self.options.force && is_target) || self.options.forcerun.contains(&tool_instance.name)
+    }
+
+    /// Returns true if the instance at the given node is pending and all its inputs are available,
+    /// or if it failed and its backoff has passed.
+    fn is_ready(&self, node: NodeIndex) -> bool {
+        match self.states[node.as_usize()] {
+            ToolInstanceState::Pending => {}
+            ToolInstanceState::Backoff { until } => return Instant::now() >= until,
+            _ => return false,
+        }
+
+        self.graph
+            .tool_instance(node)
+            .inputs
+            .iter()
+            .all(|input| match self.graph.producer(input) {
+                Some(producer) => matches!(
+                    self.states[producer.as_usize()],
+                    ToolInstanceState::Done { .. }
+                ),
+                None => Path::new(input).exists(),
+            })
+    }
+
+    /// Returns true if the instance at the given node needs to be executed.
+    /// This is the case if its last execution did not finish successfully, if it has no outputs, if any of its outputs is missing or older than any of its inputs,
+    /// if its script, inputs, container image or software environment differ from its last successful execution, or if any instance producing one of its inputs was executed.
+    fn needs_execution(&self, node: NodeIndex) -> TypemakeResult<bool> {
+        let reason = self.outdated_reason(node, |predecessor| {
+            matches!(
+                self.states[predecessor.as_usize()],
+                ToolInstanceState::Done { executed: true }
+            )
+        })?;
+        if let Some(reason) = &reason {
+            info!(
+                "Tool instance {:?} is outdated: {}",
+                self.graph.tool_instance(node).name,
+                reason
+            );
+        }
+        Ok(reason.is_some())
+    }
+
+    /// Returns the reason why the instance at the given node is outdated, or `None` if it is up to date.
+    /// `predecessor_outdated` decides if the instance at a predecessor node was or will be executed.
+    fn outdated_reason(
+        &self,
+        node: NodeIndex,
+        predecessor_outdated: impl Fn(NodeIndex) -> bool,
+    ) -> TypemakeResult<Option<String>> {
+        let tool_instance = self.graph.tool_instance(node);
+        let metadata = self
+            .state_directory
+            .metadata_store()
+            .load(&tool_instance.name)?;
+        if metadata.incomplete {
+            return Ok(Some("its last execution did not finish".to_owned()));
+        }
+
+        if tool_instance.outputs.is_empty() {
+            return Ok(Some("it has no outputs".to_owned()));
+        }
+
+        if let Some(predecessor) = self
+            .graph
+            .predecessors(node)
+            .into_iter()
+            .find(|&predecessor| predecessor_outdated(predecessor))
+        {
+            return Ok(Some(format!(
+                "its predecessor {:?} is outdated",
+                self.graph.tool_instance(predecessor).name
+            )));
+        }
+
+        // Properties may change e.g. because of a changed configuration.
+        if metadata
+            .script
+            .as_ref()
+            .is_some_and(|script| script != &tool_instance.script)
+        {
+            return Ok(Some("its script changed".to_owned()));
+        }
+        if metadata
+            .inputs
+            .as_ref()
+            .is_some_and(|inputs| inputs != &tool_instance.inputs)
+        {
+            return Ok(Some("its inputs changed".to_owned()));
+        }
+        if metadata.script.is_some() && metadata.shell != tool_instance.shell.declaration() {
+            return Ok(Some("its shell changed".to_owned()));
+        }
+        if metadata.script.is_some() && metadata.container_image != tool_instance.container {
+            return Ok(Some("its container image changed".to_owned()));
+        }
+        if let Some(environment) = &tool_instance.environment {
+            if environment.hash.is_none() {
+                return Ok(Some(format!(
+                    "its {} environment specification {:?} cannot be read",
+                    environment.kind.name(),
+                    environment.spec
+                )));
+            }
+        }
+        let environment_hash = tool_instance
+            .environment
+            .as_ref()
+            .and_then(|environment| environment.hash.clone());
+        if metadata.script.is_some() && metadata.environment_hash != environment_hash {
+            return Ok(Some("its software environment changed".to_owned()));
+        }
+
+        let mut oldest_output = None;
+        for output in &tool_instance.outputs {
+            // Removed temporary outputs need to be produced again if they are requested explicitly.
+            if self.targets.contains(output) && !Path::new(output).exists() {
+                return Ok(Some(format!("output {:?} is missing", output)));
+            }
+            let modified = if let Some(modified) = self.artifact_modified(output)? {
+                modified
+            } else {
+                return Ok(Some(format!("output {:?} is missing", output)));
+            };
+            oldest_output =
+                Some(oldest_output.map_or(modified, |oldest: SystemTime| oldest.min(modified)));
+        }
+
+        for input in &tool_instance.inputs {
+            if let Some(modified) = self.artifact_modified(input)? {
+                if modified > oldest_output.unwrap() {
+                    return Ok(Some(format!("input {:?} is newer than its outputs", input)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the modification time of the given artifact.
+    /// If the artifact is a temporary output that was removed, its modification time at the time of removal is returned.
+    /// If the artifact does not exist, `None` is returned.
+    fn artifact_modified(&self, artifact: &str) -> TypemakeResult<Option<SystemTime>> {
+        let path = Path::new(artifact);
+        if path.exists() {
+            return Ok(Some(path.metadata()?.modified()?));
+        }
+
+        if let Some(producer) = self.graph.producer(artifact) {
+            let producer_name = &self.graph.tool_instance(producer).name;
+            Ok(self
+                .state_directory
+                .metadata_store()
+                .load(producer_name)?
+                .removed_temp_outputs
+                .get(artifact)
+                .copied())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Starts the instance at the given node, or marks it as done if it is up to date.
+    /// Instances that are retried after a failure or that are forced are always started.
+    fn start(&mut self, node: NodeIndex) -> TypemakeResult<()> {
+        let tool_instance = self.graph.tool_instance(node);
+        let is_retry = matches!(
+            self.states[node.as_usize()],
+            ToolInstanceState::Backoff { .. }
+        );
+        if !is_retry && self.is_forced(node) {
+            info!("Tool instance {:?} is forced", tool_instance.name);
+        } else if !is_retry && !self.needs_execution(node)? {
+            info!("Tool instance {:?} is up to date", tool_instance.name);
+            self.states[node.as_usize()] = ToolInstanceState::Done { executed: false };
+            return self.remove_temp_outputs();
+        }
+
+        // Temporary inputs that were removed in an earlier invocation need to be produced again.
+        let mut removed_inputs = false;
+        for input in &tool_instance.inputs {
+            if let Some(producer) = self.graph.producer(input) {
+                if !Path::new(input).exists()
+                    && matches!(
+                        self.states[producer.as_usize()],
+                        ToolInstanceState::Done { executed: false }
+                    )
+                {
+                    let producer_name = &self.graph.tool_instance(producer).name;
+                    info!(
+                        "Reproducing removed temporary output {:?} of tool instance {:?}",
+                        input, producer_name
+                    );
+                    self.state_directory
+                        .metadata_store()
+                        .update(producer_name, |metadata| {
+                            metadata.removed_temp_outputs.clear()
+                        })?;
+                    self.states[producer.as_usize()] = ToolInstanceState::Pending;
+                    removed_inputs = true;
+                }
+            }
+        }
+        if removed_inputs {
+            return Ok(());
+        }
+
+        if !self.options.force {
+            let protected_outputs: Vec<_> = tool_instance
+                .outputs
+                .iter()
+                .filter(|output| {
+                    tool_instance.output_flags(output).protected && Path::new(output).exists()
+                })
+                .collect();
+            if !protected_outputs.is_empty() {
+                return Err(TypemakeError::GeneralError(format!(
+                    "Tool instance {:?} would overwrite the protected outputs {:?}. Use --force to overwrite them.",
+                    tool_instance.name, protected_outputs
+                )));
+            }
+        }
+
+        let attempt = self.attempts[node.as_usize()] + 1;
+        self.attempts[node.as_usize()] = attempt;
+        if !is_retry {
+            self.emit(Event::JobQueued {
+                instance: tool_instance.name.clone(),
+            });
+        }
+        if is_retry {
+            info!(
+                "Executing tool instance {:?} (attempt {})",
+                tool_instance.name, attempt
+            );
+        } else {
+            info!("Executing tool instance {:?}", tool_instance.name);
+        }
+        if tool_instance.script.trim().is_empty() {
+            // Instances without a script are not executed, but their declared outputs still need to exist.
+            if let Some(failure) = self.verify_outputs(node)? {
+                let tool_instance = self.graph.tool_instance(node);
+                if self.options.keep_going {
+                    warn!(
+                        "Tool instance {:?} {}, continuing with independent tool instances",
+                        tool_instance.name, failure
+                    );
+                    self.states[node.as_usize()] = ToolInstanceState::Failed {
+                        reason: failure,
+                        exit_status: None,
+                    };
+                    return Ok(());
+                }
+                return Err(TypemakeError::GeneralError(format!(
+                    "Tool instance {:?} {}",
+                    tool_instance.name, failure
+                )));
+            }
+            return self.finish(node, None);
+        }
+
+        // The software environment is created by the job itself, such that creating it does not block the scheduler.
+        let cache_directory = self.state_directory.environment_directory();
+        let (environment_creation, activation, environment_directory) =
+            match &tool_instance.environment {
+                Some(environment) => (
+                    environment.creation_script(&cache_directory)?,
+                    environment.activation(&cache_directory)?,
+                    Some(environment.directory(&cache_directory)?),
+                ),
+                None => Default::default(),
+            };
+
+        self.state_directory
+            .metadata_store()
+            .update(&tool_instance.name, |metadata| metadata.incomplete = true)?;
+        let log_path = self.prepare_log(tool_instance)?;
+        let staging_directory = self.state_directory.staging_directory(&tool_instance.name);
+        staging_directory.prepare(&tool_instance.outputs)?;
+
+        // The submitted script creates the software environment of the instance, if any,
+        // and runs the script file with the shell of the instance, after activating the environment and inside its container, if any.
+        let script_path = tool_instance
+            .shell
+            .write_script(&tool_instance.script, staging_directory.path())?;
+        let mut submitted_instance = ToolInstance {
+            script: activation + &tool_instance.shell.command(&script_path),
+            ..tool_instance.clone()
+        };
+        if let Some(image) = &tool_instance.container {
+            debug!(
+                "Executing tool instance {:?} in container {:?} with {}",
+                tool_instance.name,
+                image,
+                self.options.container_runtime.command()
+            );
+            let script = self.options.container_runtime.wrap_script(
+                &submitted_instance,
+                image,
+                &staging_directory,
+                environment_directory.as_deref(),
+            )?;
+            submitted_instance.script = script;
+        }
+        submitted_instance.script = environment_creation + &submitted_instance.script;
+        let job = self.executor.submit(
+            &submitted_instance,
+            attempt,
+            &log_path,
+            staging_directory.path(),
+        )?;
+        self.states[node.as_usize()] = ToolInstanceState::Running(job);
+        self.emit(Event::JobStarted {
+            instance: tool_instance.name.clone(),
+            attempt,
+            resources: tool_instance.resources_for_attempt(attempt),
+        });
+        Ok(())
+    }
+
+    /// Creates the directories required for the log of the given tool instance and returns the path of the log.
+    /// If the instance defines its own log path, a symlink to it is created at the default log path.
+    fn prepare_log(&self, tool_instance: &ToolInstance) -> TypemakeResult<PathBuf> {
+        let default_log_path = self.state_directory.log_path(&tool_instance.name);
+        create_dir_all(self.state_directory.log_directory())?;
+        let is_symlink = default_log_path
+            .symlink_metadata()
+            .map(|metadata| metadata.file_type().is_symlink())
+            .unwrap_or(false);
+        if is_symlink || (tool_instance.log.is_some() && default_log_path.exists()) {
+            std::fs::remove_file(&default_log_path)?;
+        }
+
+        if let Some(log_path) = &tool_instance.log {
+            let log_path = Path::new(log_path);
+            if let Some(parent) = log_path.parent() {
+                create_dir_all(parent)?;
+            }
+            std::os::unix::fs::symlink(std::env::current_dir()?.join(log_path), &default_log_path)?;
+            Ok(log_path.to_owned())
+        } else {
+            Ok(default_log_path)
+        }
+    }
+
+    /// Checks all running instances for completion.
+    /// Returns true if any instance finished.
+    fn poll(&mut self) -> TypemakeResult<bool> {
+        for node in self.graph.nodes() {
+            let termination =
+                if let ToolInstanceState::Running(job) = &mut self.states[node.as_usize()] {
+                    job.try_wait()?
+                } else {
+                    None
+                };
+
+            if let Some(mut termination) = termination {
+                let failure = if termination.timed_out {
+                    Some("timed out".to_owned())
+                } else if !termination.benchmark.success {
+                    Some(format!("failed with {}", termination.exit_status))
+                } else {
+                    let failure = self.verify_outputs(node)?;
+                    if failure.is_some() {
+                        termination.benchmark.success = false;
+                    }
+                    failure
+                };
+
+                self.record_termination(node, &termination)?;
+                if let Some(failure) = failure {
+                    let tool_instance = self.graph.tool_instance(node);
+
+                    let attempt = self.attempts[node.as_usize()];
+                    let retrying = attempt <= tool_instance.retry_policy.retries;
+                    self.emit(Event::JobFailed {
+                        instance: tool_instance.name.clone(),
+                        attempt,
+                        reason: failure.clone(),
+                        retrying,
+                        benchmark: termination.benchmark.clone(),
+                    });
+                    if retrying {
+                        let backoff = tool_instance
+                            .retry_policy
+                            .backoff_before_attempt(attempt + 1);
+                        warn!(
+                            "Tool instance {:?} {} in attempt {}, retrying in {:.2}s",
+                            tool_instance.name,
+                            failure,
+                            attempt,
+                            backoff.as_secs_f64()
+                        );
+                        self.states[node.as_usize()] = ToolInstanceState::Backoff {
+                            until: Instant::now() + backoff,
+                        };
+                        return Ok(true);
+                    }
+
+                    info!(
+                        "The script of tool instance {:?} is kept for inspection in {:?}",
+                        tool_instance.name,
+                        self.state_directory
+                            .staging_directory(&tool_instance.name)
+                            .path()
+                            .join(tool_instance.shell.script_file_name())
+                    );
+                    if self.options.keep_going {
+                        warn!(
+                            "Tool instance {:?} {} after {} attempts, continuing with independent tool instances",
+                            tool_instance.name, failure, attempt
+                        );
+                        self.states[node.as_usize()] = ToolInstanceState::Failed {
+                            reason: failure,
+                            exit_status: Some(termination.exit_status),
+                        };
+                        return Ok(true);
+                    }
+
+                    let name = tool_instance.name.clone();
+                    self.states[node.as_usize()] = ToolInstanceState::Pending;
+                    self.wait_for_running_instances()?;
+                    return Err(TypemakeError::GeneralError(format!(
+                        "Tool instance {:?} {} after {} attempts",
+                        name, failure, attempt
+                    )));
+                }
+
+                let tool_instance = self.graph.tool_instance(node);
+                let staging_directory = self.state_directory.staging_directory(&tool_instance.name);
+                staging_directory.publish(&tool_instance.outputs)?;
+                staging_directory.remove()?;
+                for output in &tool_instance.outputs {
+                    if tool_instance.output_flags(output).protected {
+                        debug!("Protecting output {:?}", output);
+                        make_read_only(Path::new(output))?;
+                    }
+                }
+                self.finish(node, Some(termination.benchmark))?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Checks that the successfully terminated instance at the given node produced all its declared outputs, and that they are valid.
+    /// Returns a description of the failure otherwise.
+    fn verify_outputs(&mut self, node: NodeIndex) -> TypemakeResult<Option<String>> {
+        let missing_outputs = self.missing_outputs(node)?;
+        if !missing_outputs.is_empty() {
+            return Ok(Some(format!(
+                "did not produce the declared outputs {:?}",
+                missing_outputs
+            )));
+        }
+        let invalid_outputs = self.invalid_outputs(node)?;
+        if !invalid_outputs.is_empty() {
+            return Ok(Some(format!(
+                "produced invalid outputs: {}",
+                invalid_outputs.join("; ")
+            )));
+        }
+        Ok(None)
+    }
+
+    /// Returns the path at which the given output of the given instance is produced.
+    /// Instances without a script are not executed in a staging directory, so their outputs are expected in place.
+    fn output_path(&self, tool_instance: &ToolInstance, output: &str) -> PathBuf {
+        if tool_instance.script.trim().is_empty() {
+            PathBuf::from(output)
+        } else {
+            self.state_directory
+                .staging_directory(&tool_instance.name)
+                .output_path(output)
+        }
+    }
+
+    /// Returns the declared outputs of the successfully terminated instance at the given node that are missing, or empty although flagged as non-empty.
+    /// Waits up to the latency wait for the outputs to appear.
+    fn missing_outputs(&self, node: NodeIndex) -> TypemakeResult<Vec<String>> {
+        let tool_instance = self.graph.tool_instance(node);
+        let deadline = Instant::now() + self.options.latency_wait;
+        loop {
+            let mut missing_outputs = Vec::new();
+            for output in &tool_instance.outputs {
+                let path = self.output_path(tool_instance, output);
+                let is_missing = match path.metadata() {
+                    Ok(metadata) if tool_instance.output_flags(output).nonempty => {
+                        if metadata.is_dir() {
+                            read_dir(&path)?.next().is_none()
+                        } else {
+                            metadata.len() == 0
+                        }
+                    }
+                    Ok(_) => false,
+                    Err(_) => true,
+                };
+                if is_missing {
+                    missing_outputs.push(output.clone());
+                }
+            }
+
+            if missing_outputs.is_empty() || Instant::now() >= deadline {
+                return Ok(missing_outputs);
+            }
+            debug!(
+                "Waiting for outputs {:?} of tool instance {:?}",
+                missing_outputs, tool_instance.name
+            );
+            std::thread::sleep(LATENCY_POLL_INTERVAL);
+        }
+    }
+
+    /// Runs the validators of the declared types of the outputs of the instance at the given node.
+    /// Returns a description of each output that is not valid.
+    fn invalid_outputs(&mut self, node: NodeIndex) -> TypemakeResult<Vec<String>> {
+        let tool_instance = self.graph.tool_instance(node);
+        let mut invalid_outputs = Vec::new();
+        for (output, type_name) in &tool_instance.output_types {
+            let validator = if let Some(validator) = self
+                .types
+                .get(type_name)
+                .and_then(|artifact_type| artifact_type.validator.source())
+            {
+                validator
+            } else {
+                continue;
+            };
+
+            // A JSON string is a valid string literal for the interpreter.
+            let path = serde_json::to_string(&self.output_path(tool_instance, output))?;
+            debug!("Validating output {:?} as {:?}", output, type_name);
+            match self
+                .interpreter
+                .evaluate(&format!("({})({})", validator, path))
+            {
+                Ok(InterpreterValue::Bool(true)) => {}
+                Ok(InterpreterValue::Bool(false)) => invalid_outputs.push(format!(
+                    "{:?} is not a valid {}",
+                    output, type_name
+                )),
+                Ok(other) => invalid_outputs.push(format!(
+                    "validator of {} returned a {} instead of a bool for {:?}",
+                    type_name,
+                    other.type_name(),
+                    output
+                )),
+                Err(error) => invalid_outputs.push(format!(
+                    "{:?} is not a valid {}: {}",
+                    output, type_name, error
+                )),
+            }
+        }
+        Ok(invalid_outputs)
+    }
+
+    /// Records the resources used by the terminated instance at the given node in the metadata store.
+    fn record_termination(
+        &self,
+        node: NodeIndex,
+        termination: &ProcessTermination,
+    ) -> TypemakeResult<()> {
+        let tool_instance = self.graph.tool_instance(node);
+        debug!(
+            "Tool instance {:?} took {:.2}s wall-clock time, {:.2}s user time, {:.2}s system time and {} bytes of memory",
+            tool_instance.name,
+            termination.benchmark.wall_time,
+            termination.benchmark.user_time,
+            termination.benchmark.system_time,
+            termination.benchmark.max_rss
+        );
+        self.state_directory
+            .metadata_store()
+            .update(&tool_instance.name, |metadata| {
+                metadata.benchmarks.push(termination.benchmark.clone())
+            })
+    }
+
+    /// Marks the instance at the given node as successfully executed, given the resources used by its last attempt if it executed a script.
+    /// If the instance is a checkpoint, the tools that could not be instantiated yet are evaluated again.
+    fn finish(&mut self, node: NodeIndex, benchmark: Option<Benchmark>) -> TypemakeResult<()> {
+        self.states[node.as_usize()] = ToolInstanceState::Done { executed: true };
+        let tool_instance = self.graph.tool_instance(node);
+        let (done, total) = self.progress();
+        info!(
+            "Finished tool instance {:?} ({} of {} tool instances done)",
+            tool_instance.name, done, total
+        );
+        self.emit(Event::JobFinished {
+            instance: tool_instance.name.clone(),
+            attempt: self.attempts[node.as_usize()],
+            benchmark,
+        });
+        let container_digest = match &tool_instance.container {
+            Some(image) => match self.options.container_runtime.image_digest(image) {
+                Ok(digest) => digest,
+                Err(error) => {
+                    warn!(
+                        "Could not determine the digest of container image {:?}: {}",
+                        image, error
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        let environment_hash = tool_instance
+            .environment
+            .as_ref()
+            .and_then(|environment| environment.hash.clone());
+        self.state_directory
+            .metadata_store()
+            .update(&tool_instance.name, |metadata| {
+                metadata.incomplete = false;
+                metadata.outputs = tool_instance.outputs.clone();
+                metadata.script = Some(tool_instance.script.clone());
+                metadata.inputs = Some(tool_instance.inputs.clone());
+                metadata.shell = tool_instance.shell.declaration();
+                metadata.container_image = tool_instance.container.clone();
+                metadata.container_digest = container_digest;
+                metadata.environment_hash = environment_hash;
+                metadata.removed_temp_outputs.clear();
+            })?;
+
+        // The re-evaluated tools may consume temporary outputs of the checkpoint, so they have to be known before removing any.
+        if tool_instance.checkpoint {
+            info!(
+                "Re-evaluating tools after checkpoint {:?}",
+                tool_instance.name
+            );
+            self.evaluate_tools()?;
+        }
+        self.remove_temp_outputs()
+    }
+
+    /// Removes all temporary outputs of finished instances whose consumers have all finished, unless they are targets.
+    /// Their modification times are recorded in the metadata store, such that their consumers are not considered outdated.
+    /// As long as some tools are not instantiated, nothing is removed, since their instances might still consume the outputs.
+    fn remove_temp_outputs(&self) -> TypemakeResult<()> {
+        if !self.uninstantiated_tools().is_empty() {
+            return Ok(());
+        }
+
+        let required = self.required_nodes();
+        for node in self.graph.nodes() {
+            if !matches!(self.states[node.as_usize()], ToolInstanceState::Done { .. }) {
+                continue;
+            }
+
+            let tool_instance = self.graph.tool_instance(node);
+            for output in &tool_instance.outputs {
+                let path = Path::new(output);
+                if !tool_instance.output_flags(output).temp
+                    || self.targets.contains(output)
+                    || !path.exists()
+                    || self.graph.consumers(output).into_iter().any(|consumer| {
+                        required[consumer.as_usize()]
+                            && !matches!(
+                                self.states[consumer.as_usize()],
+                                ToolInstanceState::Done { .. }
+                            )
+                    })
+                {
+                    continue;
+                }
+
+                info!("Removing temporary output {:?}", output);
+                let modified = path.metadata()?.modified()?;
+                if path.is_dir() {
+                    remove_dir_all(path)?;
+                } else {
+                    remove_file(path)?;
+                }
+                self.state_directory
+                    .metadata_store()
+                    .update(&tool_instance.name, |metadata| {
+                        metadata
+                            .removed_temp_outputs
+                            .insert(output.clone(), modified);
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits until all running instances have terminated, ignoring their exit status.
+    fn wait_for_running_instances(&mut self) -> TypemakeResult<()> {
+        for node in self.graph.nodes() {
+            if let ToolInstanceState::Running(job) = &mut self.states[node.as_usize()] {
+                warn!(
+                    "Waiting for running tool instance {:?} to terminate",
+                    self.graph.tool_instance(node).name
+                );
+                let termination = job.wait()?;
+                self.states[node.as_usize()] = ToolInstanceState::Pending;
+                self.record_termination(node, &termination)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Forwards the given signal to all running instances and waits for them to terminate.
+    /// Instances that do not terminate within the grace period are killed.
+    /// Their outputs stay marked as incomplete in the metadata store.
+    fn interrupt(&mut self, signal: libc::c_int) -> TypemakeResult<()> {
+        warn!(
+            "Received signal {}, terminating running tool instances",
+            signal
+        );
+        for state in &mut self.states {
+            if let ToolInstanceState::Running(job) = state {
+                job.signal(signal)?;
+            }
+        }
+
+        let deadline = Instant::now() + INTERRUPT_GRACE_PERIOD;
+        loop {
+            self.poll_terminated()?;
+            if !self
+                .states
+                .iter()
+                .any(|state| matches!(state, ToolInstanceState::Running(_)))
+            {
+                break;
+            }
+
+            if Instant::now() >= deadline {
+                warn!("Grace period expired, killing running tool instances");
+                for state in &mut self.states {
+                    if let ToolInstanceState::Running(job) = state {
+                        job.signal(libc::SIGKILL)?;
+                    }
+                }
+                self.wait_for_running_instances()?;
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        Err(TypemakeError::GeneralError(format!(
+            "Interrupted by signal {}",
+            signal
+        )))
+    }
+
+    /// Reaps all running instances that have terminated, without treating them as finished.
+    fn poll_terminated(&mut self) -> TypemakeResult<()> {
+        for node in self.graph.nodes() {
+            if let ToolInstanceState::Running(job) = &mut self.states[node.as_usize()] {
+                if let Some(termination) = job.try_wait()? {
+                    self.states[node.as_usize()] = ToolInstanceState::Pending;
+                    self.record_termination(node, &termination)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that all targets were produced after the scheduler ran out of executable instances.
+    /// Instances that were skipped because they depend on a failed instance are not reported.
+    fn check_completion(&self) -> TypemakeResult<()> {
+        let required = self.required_nodes();
+        let failed_nodes: Vec<_> = self
+            .graph
+            .nodes()
+            .into_iter()
+            .filter(|node| {
+                matches!(
+                    self.states[node.as_usize()],
+                    ToolInstanceState::Failed { .. }
+                )
+            })
+            .collect();
+        let skipped = self.with_successors(failed_nodes);
+        let mut errors = Vec::new();
+
+        for node in self.graph.nodes() {
+            if required[node.as_usize()]
+                && !skipped[node.as_usize()]
+                && matches!(self.states[node.as_usize()], ToolInstanceState::Pending)
+            {
+                let tool_instance = self.graph.tool_instance(node);
+                let missing_inputs: Vec<_> = tool_instance
+                    .inputs
+                    .iter()
+                    .filter(|input| {
+                        self.graph.producer(input).is_none() && !Path::new(input).exists()
+                    })
+                    .collect();
+                errors.push(format!(
+                    "Tool instance {:?} could not be executed, missing inputs: {:?}",
+                    tool_instance.name, missing_inputs
+                ));
+            }
+        }
+
+        for target in &self.targets {
+            if self.graph.producer(target).is_none() && !Path::new(target).exists() {
+                errors.push(format!("No tool instance produces target {:?}", target));
+            }
+        }
+
+        if self.targets.is_empty() || !errors.is_empty() {
+            for tool in self.tools.values() {
+                if self.graph.instance_node(&tool.name).is_none() {
+                    errors.push(format!(
+                        "Tool {:?} could not be instantiated: {}",
+                        tool.name,
+                        tool.evaluation_errors().join("; ")
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(TypemakeError::GeneralError(errors.join("\n")))
+        }
+    }
+}
+
+/// Removes the write permissions of the given file, or of all files in the given directory.
+fn make_read_only(path: &Path) -> TypemakeResult<()> {
+    let metadata = path.symlink_metadata()?;
+    if metadata.is_dir() {
+        for entry in read_dir(path)? {
+            make_read_only(&entry?.path())?;
+        }
+    } else if metadata.is_file() {
+        let mut permissions = metadata.permissions();
+        permissions.set_readonly(true);
+        set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
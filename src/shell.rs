@@ -0,0 +1,115 @@
+//! Shells running the scripts of tool instances.
+//!
+//! The script of a tool instance is written into a file in its staging directory, which is kept for inspection if the instance fails,
+//! and executed by the shell selected with the `shell` property of the tool.
+//! By default, scripts are run with bash in strict mode, i.e. they abort on the first failing command, on unset variables and on failures inside pipes.
+
+use crate::error::TypemakeResult;
+use crate::executor::job_script::shell_quote;
+use crate::interpreter::InterpreterValue;
+use std::convert::TryFrom;
+use std::fs::write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The name of the file in the staging directory containing the script of a tool instance, without extension.
+const SCRIPT_FILE_STEM: &str = ".typemake-script";
+/// The placeholder in a custom shell command that is replaced by the path of the script file.
+const SCRIPT_PLACEHOLDER: &str = "{script}";
+/// The commands inserted in front of scripts run with bash, enabling its strict mode.
+const BASH_STRICT_MODE: &str = "set -euo pipefail\n";
+
+/// The shell running the script of a tool instance.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Shell {
+    /// Bash in strict mode.
+    #[default]
+    Bash,
+    /// The POSIX shell `sh`.
+    Sh,
+    /// The python interpreter `python3`.
+    Python,
+    /// The R interpreter `Rscript`.
+    Rscript,
+    /// A custom command, in which the placeholder `{script}` is replaced by the path of the script file.
+    Custom(String),
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(shell: &str) -> Result<Self, Self::Err> {
+        match shell {
+            "bash" => Ok(Self::Bash),
+            "sh" => Ok(Self::Sh),
+            "python" => Ok(Self::Python),
+            "Rscript" => Ok(Self::Rscript),
+            command if command.contains(SCRIPT_PLACEHOLDER) => Ok(Self::Custom(command.to_owned())),
+            other => Err(format!(
+                "unknown shell {:?}, expected bash, sh, python, Rscript or a command containing {}",
+                other, SCRIPT_PLACEHOLDER
+            )),
+        }
+    }
+}
+
+impl TryFrom<InterpreterValue> for Shell {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        String::try_from(value)?.parse()
+    }
+}
+
+impl Shell {
+    /// Returns the value of the `shell` property selecting this shell, or `None` for the default shell bash.
+    pub fn declaration(&self) -> Option<String> {
+        match self {
+            Self::Bash => None,
+            Self::Sh => Some("sh".to_owned()),
+            Self::Python => Some("python".to_owned()),
+            Self::Rscript => Some("Rscript".to_owned()),
+            Self::Custom(command) => Some(command.clone()),
+        }
+    }
+
+    /// Returns the name of the file containing a script run by this shell.
+    pub fn script_file_name(&self) -> String {
+        match self {
+            Self::Bash | Self::Sh => format!("{}.sh", SCRIPT_FILE_STEM),
+            Self::Python => format!("{}.py", SCRIPT_FILE_STEM),
+            Self::Rscript => format!("{}.R", SCRIPT_FILE_STEM),
+            Self::Custom(_) => SCRIPT_FILE_STEM.to_owned(),
+        }
+    }
+
+    /// Writes the given script into a file in the given directory and returns the absolute path of the file.
+    /// For bash, strict mode is enabled at the beginning of the script.
+    pub fn write_script(&self, script: &str, directory: &Path) -> TypemakeResult<PathBuf> {
+        let path = std::env::current_dir()?
+            .join(directory)
+            .join(self.script_file_name());
+        let mut content = match self {
+            Self::Bash => BASH_STRICT_MODE.to_owned(),
+            _ => String::new(),
+        };
+        content.push_str(script);
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Returns the shell command running the script file at the given path with this shell.
+    pub fn command(&self, script_path: &Path) -> String {
+        let script_path = shell_quote(script_path);
+        match self {
+            Self::Bash => format!("bash {}", script_path),
+            Self::Sh => format!("sh {}", script_path),
+            Self::Python => format!("python3 {}", script_path),
+            Self::Rscript => format!("Rscript {}", script_path),
+            Self::Custom(command) => command.replace(SCRIPT_PLACEHOLDER, &script_path),
+        }
+    }
+}
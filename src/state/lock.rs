@@ -0,0 +1,200 @@
+//! Locks preventing concurrent typemake processes from producing the same artifacts.
+
+use crate::error::{TypemakeError, TypemakeResult};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_file, write, File};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+/// The name of the file used to serialise access to the lock directory.
+const MUTEX_FILE_NAME: &str = ".mutex";
+/// The file extension of lock files.
+const LOCK_FILE_EXTENSION: &str = "lock";
+
+/// The content of a lock file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LockInfo {
+    /// The id of the process holding the lock.
+    pub pid: u32,
+    /// The name of the host running the process holding the lock.
+    pub host: String,
+    /// The artifacts produced by the process holding the lock.
+    pub artifacts: BTreeSet<String>,
+}
+
+impl LockInfo {
+    /// Returns true if the lock was created by a process on this host that is not running anymore.
+    /// Locks of other hosts are never considered stale, since their processes cannot be checked.
+    pub fn is_stale(&self) -> bool {
+        if self.host != hostname() {
+            return false;
+        }
+
+        // Safety: kill with signal zero only checks for the existence of the process.
+        if unsafe { libc::kill(self.pid as libc::pid_t, 0) } == 0 {
+            false
+        } else {
+            std::io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH)
+        }
+    }
+}
+
+/// A lock on the artifacts produced by this typemake process.
+/// The lock is released when this is dropped.
+#[derive(Debug)]
+pub struct WorkflowLock {
+    /// The directory containing all lock files.
+    lock_directory: PathBuf,
+    /// The lock file of this process.
+    lock_path: PathBuf,
+    /// The content of the lock file of this process.
+    info: LockInfo,
+}
+
+impl WorkflowLock {
+    /// Acquires a lock on the given artifacts.
+    /// Returns an error if another typemake process holds a lock on any of them.
+    pub fn acquire(lock_directory: PathBuf, artifacts: BTreeSet<String>) -> TypemakeResult<Self> {
+        let info = LockInfo {
+            pid: std::process::id(),
+            host: hostname(),
+            artifacts: BTreeSet::new(),
+        };
+        let lock_path = lock_directory.join(format!(
+            "{}-{}.{}",
+            info.host, info.pid, LOCK_FILE_EXTENSION
+        ));
+        let mut lock = Self {
+            lock_directory,
+            lock_path,
+            info,
+        };
+        lock.extend(artifacts)?;
+        Ok(lock)
+    }
+
+    /// Adds the given artifacts to the lock.
+    /// Returns an error if another typemake process holds a lock on any of them.
+    pub fn extend(&mut self, artifacts: BTreeSet<String>) -> TypemakeResult<()> {
+        if artifacts.is_subset(&self.info.artifacts) && self.lock_path.exists() {
+            return Ok(());
+        }
+
+        let _mutex = LockDirectoryMutex::lock(&self.lock_directory)?;
+        for (path, other) in read_locks(&self.lock_directory)? {
+            if path == self.lock_path {
+                continue;
+            }
+
+            let overlap: Vec<_> = other.artifacts.intersection(&artifacts).collect();
+            if !overlap.is_empty() {
+                return Err(TypemakeError::GeneralError(format!(
+                    "Another typemake process (pid {} on host {}{}) is producing the artifacts {:?}. \
+                    If that process is not running anymore, remove its lock with `typemake unlock`.",
+                    other.pid,
+                    other.host,
+                    if other.is_stale() {
+                        ", which is not running anymore"
+                    } else {
+                        ""
+                    },
+                    overlap
+                )));
+            }
+        }
+
+        self.info.artifacts.extend(artifacts);
+        write(&self.lock_path, serde_json::to_string_pretty(&self.info)?)?;
+        debug!("Locked {} artifacts", self.info.artifacts.len());
+        Ok(())
+    }
+}
+
+impl Drop for WorkflowLock {
+    fn drop(&mut self) {
+        match remove_file(&self.lock_path) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Could not remove lock {:?}: {}", self.lock_path, error)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Removes the locks in the given directory.
+/// If `force` is false, only stale locks are removed.
+/// Returns the removed locks.
+pub fn remove_locks(lock_directory: &Path, force: bool) -> TypemakeResult<Vec<LockInfo>> {
+    let _mutex = LockDirectoryMutex::lock(lock_directory)?;
+    let mut removed = Vec::new();
+    for (path, lock) in read_locks(lock_directory)? {
+        if force || lock.is_stale() {
+            remove_file(path)?;
+            removed.push(lock);
+        }
+    }
+    Ok(removed)
+}
+
+/// Returns the artifacts locked by typemake processes that may still be running.
+pub fn locked_artifacts(lock_directory: &Path) -> TypemakeResult<BTreeSet<String>> {
+    if !lock_directory.exists() {
+        return Ok(BTreeSet::new());
+    }
+
+    Ok(read_locks(lock_directory)?
+        .into_iter()
+        .filter(|(_, lock)| !lock.is_stale())
+        .flat_map(|(_, lock)| lock.artifacts)
+        .collect())
+}
+
+/// Reads all lock files in the given directory.
+fn read_locks(lock_directory: &Path) -> TypemakeResult<Vec<(PathBuf, LockInfo)>> {
+    let mut locks = Vec::new();
+    for entry in read_dir(lock_directory)? {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str()) == Some(LOCK_FILE_EXTENSION) {
+            let lock = serde_json::from_str(&read_to_string(&path)?)?;
+            locks.push((path, lock));
+        }
+    }
+    locks.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(locks)
+}
+
+/// An exclusive `flock` on a file in the lock directory, such that only one process at a time inspects and modifies the locks.
+struct LockDirectoryMutex {
+    /// The locked file, which is unlocked when it is closed.
+    _file: File,
+}
+
+impl LockDirectoryMutex {
+    /// Blocks until the mutex of the given lock directory is acquired.
+    /// Creates the lock directory if it does not exist.
+    fn lock(lock_directory: &Path) -> TypemakeResult<Self> {
+        create_dir_all(lock_directory)?;
+        let file = File::create(lock_directory.join(MUTEX_FILE_NAME))?;
+        // Safety: flock has no memory safety requirements.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(Self { _file: file })
+    }
+}
+
+/// Returns the name of this host.
+fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+    // Safety: the buffer is valid for writes of its length.
+    if unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) } < 0 {
+        return "unknown".to_owned();
+    }
+    let length = buffer
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..length]).into_owned()
+}
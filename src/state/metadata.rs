@@ -0,0 +1,156 @@
+//! The metadata store, recording information about tool instances across invocations.
+
+use crate::error::TypemakeResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_file, write};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// The file extension of metadata files.
+const METADATA_FILE_EXTENSION: &str = "json";
+
+/// The metadata recorded for a single tool instance.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ToolInstanceMetadata {
+    /// The measurements of all executions of the tool instance, in chronological order.
+    #[serde(default)]
+    pub benchmarks: Vec<Benchmark>,
+    /// True if the outputs of the instance may be incomplete.
+    /// This is set when an execution of the instance starts, and reset once it finishes successfully,
+    /// such that interrupted, failed and crashed executions are detected on the next invocation.
+    #[serde(default)]
+    pub incomplete: bool,
+    /// The outputs produced by the last successful execution of the instance.
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// The evaluated script of the last successful execution of the instance,
+    /// or `None` if it was not recorded.
+    #[serde(default)]
+    pub script: Option<String>,
+    /// The inputs of the last successful execution of the instance,
+    /// or `None` if they were not recorded.
+    #[serde(default)]
+    pub inputs: Option<Vec<String>>,
+    /// The shell declared by the tool in the last successful execution of the instance,
+    /// or `None` if the tool used the default shell bash.
+    #[serde(default)]
+    pub shell: Option<String>,
+    /// The container image of the last successful execution of the instance, if it was executed in a container.
+    #[serde(default)]
+    pub container_image: Option<String>,
+    /// The digest of the container image of the last successful execution of the instance,
+    /// or `None` if it was not executed in a container or the digest could not be determined.
+    #[serde(default)]
+    pub container_digest: Option<String>,
+    /// The hash of the software environment of the last successful execution of the instance, if it had one.
+    #[serde(default)]
+    pub environment_hash: Option<String>,
+    /// The temporary outputs of the instance that were removed after all their consumers finished,
+    /// mapped to their modification times at the time of removal.
+    #[serde(default)]
+    pub removed_temp_outputs: BTreeMap<String, SystemTime>,
+}
+
+/// The resources used by a single execution of a tool instance.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Benchmark {
+    /// The time at which the execution started, in seconds since the unix epoch.
+    pub started: u64,
+    /// The wall-clock time of the execution in seconds.
+    pub wall_time: f64,
+    /// The CPU time spent in user mode in seconds.
+    pub user_time: f64,
+    /// The CPU time spent in kernel mode in seconds.
+    pub system_time: f64,
+    /// The peak resident set size in bytes.
+    pub max_rss: u64,
+    /// True if the execution terminated successfully.
+    pub success: bool,
+}
+
+/// A store of metadata files, one per tool instance.
+#[derive(Debug, Clone)]
+pub struct MetadataStore {
+    /// The directory containing the metadata files.
+    directory: PathBuf,
+}
+
+impl MetadataStore {
+    /// Creates a handle to the metadata store in the given directory.
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    /// Returns the path of the metadata file of the given tool instance.
+    fn metadata_path(&self, instance_name: &str) -> PathBuf {
+        self.directory
+            .join(format!("{}.{}", instance_name, METADATA_FILE_EXTENSION))
+    }
+
+    /// Loads the metadata of the given tool instance.
+    /// If no metadata was recorded for the instance, empty metadata is returned.
+    pub fn load(&self, instance_name: &str) -> TypemakeResult<ToolInstanceMetadata> {
+        let metadata_path = self.metadata_path(instance_name);
+        if metadata_path.exists() {
+            Ok(serde_json::from_str(&read_to_string(metadata_path)?)?)
+        } else {
+            Ok(Default::default())
+        }
+    }
+
+    /// Stores the metadata of the given tool instance, replacing any previously stored metadata.
+    pub fn store(
+        &self,
+        instance_name: &str,
+        metadata: &ToolInstanceMetadata,
+    ) -> TypemakeResult<()> {
+        create_dir_all(&self.directory)?;
+        write(
+            self.metadata_path(instance_name),
+            serde_json::to_string_pretty(metadata)?,
+        )?;
+        Ok(())
+    }
+
+    /// Applies the given modification to the metadata of the given tool instance.
+    pub fn update(
+        &self,
+        instance_name: &str,
+        modification: impl FnOnce(&mut ToolInstanceMetadata),
+    ) -> TypemakeResult<()> {
+        let mut metadata = self.load(instance_name)?;
+        modification(&mut metadata);
+        self.store(instance_name, &metadata)
+    }
+
+    /// Removes the metadata of the given tool instance, if any was recorded.
+    pub fn remove(&self, instance_name: &str) -> TypemakeResult<()> {
+        let metadata_path = self.metadata_path(instance_name);
+        if metadata_path.exists() {
+            remove_file(metadata_path)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the names of all tool instances that have metadata recorded, in sorted order.
+    pub fn instance_names(&self) -> TypemakeResult<Vec<String>> {
+        if !self.directory.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut instance_names = Vec::new();
+        for entry in read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str())
+                == Some(METADATA_FILE_EXTENSION)
+            {
+                if let Some(instance_name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    instance_names.push(instance_name.to_owned());
+                }
+            }
+        }
+        instance_names.sort();
+        Ok(instance_names)
+    }
+}
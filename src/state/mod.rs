@@ -0,0 +1,76 @@
+//! The state directory, in which typemake keeps information between invocations.
+
+use crate::state::metadata::MetadataStore;
+use crate::state::staging::StagingDirectory;
+use std::path::PathBuf;
+
+pub mod lock;
+pub mod metadata;
+pub mod staging;
+
+/// The name of the subdirectory containing the logs of tool instances.
+const LOG_DIRECTORY_NAME: &str = "logs";
+/// The name of the subdirectory containing the locks of running typemake processes.
+const LOCK_DIRECTORY_NAME: &str = "locks";
+/// The name of the subdirectory containing the metadata store.
+const METADATA_DIRECTORY_NAME: &str = "metadata";
+/// The name of the subdirectory containing the staging directories of tool instances.
+const STAGING_DIRECTORY_NAME: &str = "staging";
+/// The name of the subdirectory containing the scripts of SLURM job arrays.
+const SLURM_DIRECTORY_NAME: &str = "slurm";
+/// The name of the subdirectory containing the software environments of tools.
+const ENVIRONMENT_DIRECTORY_NAME: &str = "environments";
+
+/// The state directory of typemake.
+/// It is created lazily, i.e. only its path is known until something is written into it.
+#[derive(Debug, Clone)]
+pub struct StateDirectory {
+    /// The path of the state directory.
+    path: PathBuf,
+}
+
+impl StateDirectory {
+    /// Creates a handle to the state directory at the given path.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path of the directory containing the logs of tool instances.
+    pub fn log_directory(&self) -> PathBuf {
+        self.path.join(LOG_DIRECTORY_NAME)
+    }
+
+    /// Returns the default path of the log of the given tool instance.
+    /// If the tool instance defines its own log path, this path is a symlink to it.
+    pub fn log_path(&self, instance_name: &str) -> PathBuf {
+        self.log_directory().join(format!("{}.log", instance_name))
+    }
+
+    /// Returns the path of the directory containing the locks of running typemake processes.
+    pub fn lock_directory(&self) -> PathBuf {
+        self.path.join(LOCK_DIRECTORY_NAME)
+    }
+
+    /// Returns the staging directory of the given tool instance.
+    pub fn staging_directory(&self, instance_name: &str) -> StagingDirectory {
+        StagingDirectory::new(
+            self.path.join(STAGING_DIRECTORY_NAME).join(instance_name),
+            self.path.clone(),
+        )
+    }
+
+    /// Returns the path of the directory containing the scripts and the output of SLURM job arrays.
+    pub fn slurm_directory(&self) -> PathBuf {
+        self.path.join(SLURM_DIRECTORY_NAME)
+    }
+
+    /// Returns the path of the directory caching the software environments of tools.
+    pub fn environment_directory(&self) -> PathBuf {
+        self.path.join(ENVIRONMENT_DIRECTORY_NAME)
+    }
+
+    /// Returns the metadata store inside the state directory.
+    pub fn metadata_store(&self) -> MetadataStore {
+        MetadataStore::new(self.path.join(METADATA_DIRECTORY_NAME))
+    }
+}
@@ -0,0 +1,189 @@
+//! Staging directories, in which tool instances produce their outputs before they are published.
+//!
+//! A tool instance is executed with its staging directory as working directory.
+//! The staging directory mirrors the working directory of typemake via symlinks, except for the declared outputs and the directories containing them,
+//! such that inputs can be read as usual while outputs are written into the staging directory.
+//! Only after the instance finishes successfully, its outputs are moved to their final location.
+//! Outputs that are not relative paths below the working directory are not staged.
+
+use crate::error::TypemakeResult;
+use log::debug;
+use std::collections::BTreeSet;
+use std::fs::{
+    copy, create_dir, create_dir_all, read_dir, remove_dir_all, remove_file, rename,
+    symlink_metadata,
+};
+use std::os::unix::fs::symlink;
+use std::path::{Component, Path, PathBuf};
+
+/// The staging directory of a tool instance.
+#[derive(Debug, Clone)]
+pub struct StagingDirectory {
+    /// The path of the staging directory.
+    path: PathBuf,
+    /// The path of the state directory, which is not mirrored into the staging directory.
+    state_directory: PathBuf,
+}
+
+impl StagingDirectory {
+    /// Creates a handle to the staging directory at the given path.
+    pub fn new(path: PathBuf, state_directory: PathBuf) -> Self {
+        Self {
+            path,
+            state_directory,
+        }
+    }
+
+    /// Returns the path of the staging directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path at which the given output is produced, which is inside the staging directory if the output is staged.
+    pub fn output_path(&self, output: &str) -> PathBuf {
+        match staged_path(output) {
+            Some(relative) => self.path.join(relative),
+            None => PathBuf::from(output),
+        }
+    }
+
+    /// Creates an empty staging directory for a tool instance with the given outputs, replacing any leftovers of previous executions,
+    /// and mirrors the working directory into it.
+    pub fn prepare(&self, outputs: &[String]) -> TypemakeResult<()> {
+        self.remove()?;
+        create_dir_all(&self.path)?;
+
+        let working_directory = std::env::current_dir()?;
+        let state_directory = self.state_directory.canonicalize()?;
+        let staged_outputs: BTreeSet<_> = outputs
+            .iter()
+            .filter_map(|output| staged_path(output))
+            .collect();
+        let output_directories: BTreeSet<_> = staged_outputs
+            .iter()
+            .flat_map(|output| output.ancestors().skip(1))
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .map(Path::to_owned)
+            .collect();
+
+        let mut stack = vec![PathBuf::new()];
+        while let Some(directory) = stack.pop() {
+            let source_directory = working_directory.join(&directory);
+            if !source_directory.is_dir() {
+                continue;
+            }
+
+            for entry in read_dir(&source_directory)? {
+                let source = entry?.path();
+                let relative = directory.join(source.file_name().unwrap());
+                if staged_outputs.contains(&relative)
+                    || source.canonicalize().ok().as_ref() == Some(&state_directory)
+                {
+                    continue;
+                }
+
+                if output_directories.contains(&relative) && source.is_dir() {
+                    create_dir(self.path.join(&relative))?;
+                    stack.push(relative);
+                } else {
+                    symlink(&source, self.path.join(&relative))?;
+                }
+            }
+        }
+
+        for output in &staged_outputs {
+            if let Some(parent) = output.parent() {
+                create_dir_all(self.path.join(parent))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the given outputs from the staging directory to their final location, replacing existing files.
+    /// Outputs that were not produced are skipped.
+    pub fn publish(&self, outputs: &[String]) -> TypemakeResult<()> {
+        for output in outputs {
+            let relative = if let Some(relative) = staged_path(output) {
+                relative
+            } else {
+                continue;
+            };
+            let staged = self.path.join(&relative);
+            if symlink_metadata(&staged).is_err() {
+                continue;
+            }
+
+            if let Some(parent) = relative.parent() {
+                create_dir_all(parent)?;
+            }
+            if relative.is_dir() && !relative.symlink_metadata()?.file_type().is_symlink() {
+                remove_dir_all(&relative)?;
+            }
+            debug!("Publishing output {:?}", relative);
+            if let Err(error) = rename(&staged, &relative) {
+                if error.raw_os_error() != Some(libc::EXDEV) {
+                    return Err(error.into());
+                }
+
+                // The staging directory is on a different filesystem, so copy the output next to its final location first.
+                let temporary = relative.with_file_name(format!(
+                    ".{}.typemake",
+                    relative.file_name().unwrap().to_string_lossy()
+                ));
+                copy_recursively(&staged, &temporary)?;
+                rename(&temporary, &relative)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the staging directory if it exists.
+    pub fn remove(&self) -> TypemakeResult<()> {
+        if symlink_metadata(&self.path).is_ok() {
+            remove_dir_all(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the given output path if it is a relative path below the working directory, and `None` otherwise.
+fn staged_path(output: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(output).components() {
+        match component {
+            Component::Normal(component) => path.push(component),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if path.as_os_str().is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+/// Copies the given file or directory, replacing the destination if it exists.
+fn copy_recursively(source: &Path, destination: &Path) -> TypemakeResult<()> {
+    if let Ok(metadata) = symlink_metadata(destination) {
+        if metadata.is_dir() {
+            remove_dir_all(destination)?;
+        } else {
+            remove_file(destination)?;
+        }
+    }
+
+    let metadata = symlink_metadata(source)?;
+    if metadata.file_type().is_symlink() {
+        symlink(std::fs::read_link(source)?, destination)?;
+    } else if metadata.is_dir() {
+        create_dir(destination)?;
+        for entry in read_dir(source)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+    } else {
+        copy(source, destination)?;
+    }
+    Ok(())
+}
@@ -1,15 +1,24 @@
 //! Types describing a typemake workflow.
 
-use traitgraph::interface::GraphBase;
+use crate::environment::{Environment, EnvironmentKind};
+use crate::error::{TypemakeError, TypemakeResult};
+use crate::interpreter::{Interpreter, InterpreterValue};
+use crate::shell::Shell;
+use serde::Serialize;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+use traitgraph::implementation::petgraph_impl::petgraph::graph::DiGraph;
+use traitgraph::interface::{DynamicGraph, GraphBase};
 
 /// The stage of a value of a tool property.
 ///
 /// The value of a tool property is computed using the script interpreter, but its evaluation is allowed to fail.
 /// Such a failed state needs to be captured to indicate that the tool is not usable yet in the given configuration.
-#[derive(Eq, PartialEq, Debug, Clone)]
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
 pub enum ToolPropertyStage<PreliminaryType, FinalType = PreliminaryType> {
     /// The property is not defined on the tool.
+    #[default]
     Empty,
     /// The property is defined on the tool, but no attempt at evaluation was made up to now.
     String,
@@ -19,12 +28,6 @@ pub enum ToolPropertyStage<PreliminaryType, FinalType = PreliminaryType> {
     Final(FinalType),
 }
 
-impl<S, T> Default for ToolPropertyStage<S, T> {
-    fn default() -> Self {
-        Self::Empty
-    }
-}
-
 /// A property value of a tool.
 #[derive(Default, Eq, PartialEq, Debug, Clone)]
 pub struct ToolProperty<PreliminaryType, FinalType = PreliminaryType> {
@@ -55,12 +58,82 @@ impl<PreliminaryType, FinalType> ToolProperty<PreliminaryType, FinalType> {
     {
         self.value_stage == ToolPropertyStage::Empty
     }
+
+    /// Returns the string defining the property in the typefile, if the property is defined.
+    pub fn source(&self) -> Option<&str>
+    where
+        PreliminaryType: PartialEq,
+        FinalType: PartialEq,
+    {
+        if self.is_empty() {
+            None
+        } else {
+            Some(&self.string_value)
+        }
+    }
+
+    /// Returns the preliminary value of the property, if its evaluation failed.
+    pub fn preliminary_value(&self) -> Option<&PreliminaryType> {
+        match &self.value_stage {
+            ToolPropertyStage::Preliminary(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the final value of the property.
+    /// If the property is empty, the default value of the final type is returned.
+    /// If the property was not evaluated successfully, `None` is returned.
+    pub fn final_value_or_default(&self) -> Option<FinalType>
+    where
+        FinalType: Default + Clone,
+    {
+        match &self.value_stage {
+            ToolPropertyStage::Empty => Some(FinalType::default()),
+            ToolPropertyStage::Final(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<FinalType: TryFrom<InterpreterValue, Error = String>> ToolProperty<String, FinalType> {
+    /// Evaluates the string value of the property using the given interpreter.
+    /// Empty and final properties are left untouched.
+    /// If the evaluation fails, the property becomes preliminary, with the error message as value.
+    pub fn evaluate<InterpreterType: Interpreter>(&mut self, interpreter: &mut InterpreterType) {
+        self.evaluate_with(|expression| interpreter.evaluate(expression));
+    }
+
+    /// Evaluates the string value of the property like `evaluate`, but as a declaration of input or output artifacts.
+    pub fn evaluate_artifacts<InterpreterType: Interpreter>(
+        &mut self,
+        interpreter: &mut InterpreterType,
+    ) {
+        self.evaluate_with(|expression| interpreter.evaluate_artifacts(expression));
+    }
+
+    /// Evaluates the string value of the property with the given function.
+    fn evaluate_with(&mut self, evaluate: impl FnOnce(&str) -> TypemakeResult<InterpreterValue>) {
+        if matches!(
+            self.value_stage,
+            ToolPropertyStage::Empty | ToolPropertyStage::Final(_)
+        ) {
+            return;
+        }
+
+        self.value_stage = match evaluate(&self.string_value)
+            .map_err(|error| error.to_string())
+            .and_then(FinalType::try_from)
+        {
+            Ok(value) => ToolPropertyStage::Final(value),
+            Err(error) => ToolPropertyStage::Preliminary(error),
+        };
+    }
 }
 
 /// A tool definition.
 /// A tool is the basic building block of a workflow.
 /// It describes how files with certain properties are transformed into files with other properties.
-#[derive(Default, Eq, PartialEq, Debug, Clone)]
+#[derive(Default, PartialEq, Debug, Clone)]
 pub struct Tool {
     /// Each tool has a unique name.
     pub name: String,
@@ -69,18 +142,581 @@ pub struct Tool {
     /// Typically this would be a bash interpreter executing another program or a set of programs.
     pub script: ToolProperty<String>,
 
-    //pub input: ToolProperty<ToolInputDefinition>,
+    /// The shell running the script of the tool.
+    /// If not set, the script is run with bash in strict mode.
+    pub shell: ToolProperty<String, Shell>,
+
+    /// The artifacts required by the tool.
+    pub input: ToolProperty<String, Vec<Input>>,
+
+    /// The artifacts produced by the tool.
+    pub output: ToolProperty<String, Vec<Output>>,
+
+    /// The path of the file capturing the output of the tool.
+    /// If not set, the output is captured in the state directory.
+    pub log: ToolProperty<String, Option<String>>,
+
+    /// The number of threads used by the tool.
+    pub threads: ToolProperty<String, Option<u64>>,
+
+    /// The amount of memory used by the tool, in megabytes.
+    pub memory: ToolProperty<String, Option<u64>>,
+
+    /// The time in seconds after which an instance of the tool is killed.
+    pub timeout: ToolProperty<String, Option<f64>>,
+
+    /// The number of times a failed instance of the tool is executed again.
+    pub retries: ToolProperty<String, u64>,
 
-    //pub output: ToolProperty<ToolOutputDefinition>,
+    /// The delay in seconds before the first retry of a failed instance, which is doubled for each further retry.
+    pub retry_backoff: ToolProperty<String, f64>,
+
+    /// The factor by which the memory and timeout of an instance are multiplied for each retry.
+    pub retry_scaling: ToolProperty<String, Option<f64>>,
+
+    /// The container image in which the script of the tool is executed.
+    /// If not set, the script is executed directly on the host.
+    pub container: ToolProperty<String, Option<String>>,
+
+    /// The path of the conda environment file specifying the software environment of the tool.
+    pub conda: ToolProperty<String, Option<String>>,
+
+    /// The path of the pip requirements file specifying the python virtual environment of the tool.
+    pub venv: ToolProperty<String, Option<String>>,
+
+    /// The path of the nix expression specifying the software environment of the tool.
+    pub nix: ToolProperty<String, Option<String>>,
+
+    /// If set, the properties of all tools that could not be evaluated yet are evaluated again after an instance of this tool finished.
+    pub checkpoint: bool,
+}
+
+impl Tool {
+    /// Evaluates all properties of the tool using the given interpreter.
+    pub fn evaluate<InterpreterType: Interpreter>(&mut self, interpreter: &mut InterpreterType) {
+        self.script.evaluate(interpreter);
+        self.shell.evaluate(interpreter);
+        self.input.evaluate_artifacts(interpreter);
+        self.output.evaluate_artifacts(interpreter);
+        self.log.evaluate(interpreter);
+        self.threads.evaluate(interpreter);
+        self.memory.evaluate(interpreter);
+        self.timeout.evaluate(interpreter);
+        self.retries.evaluate(interpreter);
+        self.retry_backoff.evaluate(interpreter);
+        self.retry_scaling.evaluate(interpreter);
+        self.container.evaluate(interpreter);
+        self.conda.evaluate(interpreter);
+        self.venv.evaluate(interpreter);
+        self.nix.evaluate(interpreter);
+    }
+
+    /// Returns the names of the properties declaring a software environment that are defined on the tool.
+    /// At most one of them is allowed.
+    pub fn environment_properties(&self) -> Vec<&'static str> {
+        [
+            (EnvironmentKind::Conda, &self.conda),
+            (EnvironmentKind::Venv, &self.venv),
+            (EnvironmentKind::Nix, &self.nix),
+        ]
+        .iter()
+        .filter(|(_, spec)| !spec.is_empty())
+        .map(|(kind, _)| kind.name())
+        .collect()
+    }
+
+    /// Returns the software environment of the tool, or `None` if any of the properties declaring it could not be evaluated.
+    fn environment(&self) -> Option<Option<Environment>> {
+        let mut environment = None;
+        for (kind, spec) in [
+            (EnvironmentKind::Conda, &self.conda),
+            (EnvironmentKind::Venv, &self.venv),
+            (EnvironmentKind::Nix, &self.nix),
+        ] {
+            if let Some(spec) = spec.final_value_or_default()? {
+                environment = Some(Environment::new(kind, spec));
+            }
+        }
+        Some(environment)
+    }
+
+    /// Creates an instance of this tool, if all its properties were evaluated successfully.
+    pub fn instantiate(&self) -> Option<ToolInstance> {
+        let inputs = self.input.final_value_or_default()?;
+        let outputs = self.output.final_value_or_default()?;
+        Some(ToolInstance {
+            name: self.name.clone(),
+            script: self.script.final_value_or_default()?,
+            shell: self.shell.final_value_or_default()?,
+            inputs: inputs.iter().map(|input| input.path.clone()).collect(),
+            input_types: inputs
+                .into_iter()
+                .filter_map(|input| Some((input.path, input.artifact_type?)))
+                .collect(),
+            outputs: outputs.iter().map(|output| output.path.clone()).collect(),
+            output_types: outputs
+                .iter()
+                .filter_map(|output| Some((output.path.clone(), output.artifact_type.clone()?)))
+                .collect(),
+            output_flags: outputs
+                .into_iter()
+                .map(|output| (output.path, output.flags))
+                .collect(),
+            log: self.log.final_value_or_default()?,
+            resources: Resources {
+                threads: self.threads.final_value_or_default()?,
+                memory: self.memory.final_value_or_default()?,
+                timeout: self.timeout.final_value_or_default()?,
+            },
+            retry_policy: RetryPolicy {
+                retries: self.retries.final_value_or_default()?,
+                backoff: self.retry_backoff.final_value_or_default()?,
+                scaling: self.retry_scaling.final_value_or_default()?.unwrap_or(1.0),
+            },
+            container: self.container.final_value_or_default()?,
+            environment: self.environment()?,
+            checkpoint: self.checkpoint,
+        })
+    }
+
+    /// Returns the error messages of all properties that could not be evaluated, prefixed by the property name.
+    pub fn evaluation_errors(&self) -> Vec<String> {
+        [
+            ("interpreter", self.script.preliminary_value()),
+            ("shell", self.shell.preliminary_value()),
+            ("input", self.input.preliminary_value()),
+            ("output", self.output.preliminary_value()),
+            ("log", self.log.preliminary_value()),
+            ("threads", self.threads.preliminary_value()),
+            ("memory", self.memory.preliminary_value()),
+            ("timeout", self.timeout.preliminary_value()),
+            ("retries", self.retries.preliminary_value()),
+            ("retry_backoff", self.retry_backoff.preliminary_value()),
+            ("retry_scaling", self.retry_scaling.preliminary_value()),
+            ("container", self.container.preliminary_value()),
+            ("conda", self.conda.preliminary_value()),
+            ("venv", self.venv.preliminary_value()),
+            ("nix", self.nix.preliminary_value()),
+        ]
+        .iter()
+        .filter_map(|(property_name, error)| {
+            error.map(|error| format!("{}: {}", property_name, error))
+        })
+        .collect()
+    }
 }
 
-// TODO create separate workflow definition and workflow instantiation types.
+/// An instance of a tool, with all its properties evaluated.
+/// It corresponds to a single execution of the tool's script.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ToolInstance {
+    /// The name of the instance.
+    /// Each tool is instantiated at most once, so this equals the name of the tool.
+    pub name: String,
+    /// The script executed by the instance.
+    pub script: String,
+    /// The shell running the script of the instance.
+    pub shell: Shell,
+    /// The artifacts required by the instance.
+    pub inputs: Vec<String>,
+    /// The declared types of the artifacts required by the instance.
+    pub input_types: BTreeMap<String, String>,
+    /// The artifacts produced by the instance.
+    pub outputs: Vec<String>,
+    /// The declared types of the artifacts produced by the instance.
+    pub output_types: BTreeMap<String, String>,
+    /// The flags of the artifacts produced by the instance.
+    pub output_flags: BTreeMap<String, OutputFlags>,
+    /// The path of the file capturing the output of the instance, if it differs from the default.
+    pub log: Option<String>,
+    /// The resources requested by the instance for its first attempt.
+    pub resources: Resources,
+    /// Describes how the instance is retried if it fails.
+    pub retry_policy: RetryPolicy,
+    /// The container image in which the script of the instance is executed, if any.
+    pub container: Option<String>,
+    /// The software environment in which the script of the instance is executed, if any.
+    pub environment: Option<Environment>,
+    /// True if the instance was created from a checkpoint tool.
+    pub checkpoint: bool,
+}
+
+impl ToolInstance {
+    /// Returns the flags of the given output of this instance.
+    pub fn output_flags(&self, output: &str) -> OutputFlags {
+        self.output_flags.get(output).cloned().unwrap_or_default()
+    }
+
+    /// Returns the resources requested by the given attempt of executing this instance, starting at one.
+    pub fn resources_for_attempt(&self, attempt: u64) -> Resources {
+        let factor = self
+            .retry_policy
+            .scaling
+            .powi(attempt.saturating_sub(1) as i32);
+        Resources {
+            threads: self.resources.threads,
+            memory: self
+                .resources
+                .memory
+                .map(|memory| (memory as f64 * factor).ceil() as u64),
+            timeout: self.resources.timeout.map(|timeout| timeout * factor),
+        }
+    }
+}
+
+/// A type of artifacts, describing properties shared by all artifacts of the type.
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct ArtifactType {
+    /// Each type has a unique name.
+    pub name: String,
 
-/*pub struct ToolInstance {
+    /// A function receiving the path of an artifact and returning true if the artifact is a valid instance of the type.
+    pub validator: ToolProperty<String>,
+}
 
+/// An artifact required by a tool, together with its declared type.
+///
+/// In the typefile, an input is either given as a path, or as a dict containing the path under the key `path` and the name of its type under the key `type`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Input {
+    /// The path of the artifact.
+    pub path: String,
+    /// The name of the declared type of the artifact.
+    pub artifact_type: Option<String>,
 }
 
-pub struct WorkflowGraph<Graph: GraphBase<NodeData = ToolInstance>> {
+/// An artifact produced by a tool, together with its declared type and flags.
+///
+/// In the typefile, an output is either given as a path, or as a dict containing the path under the key `path`, the name of its type under the key `type`,
+/// and the flags as booleans under their names.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Output {
+    /// The path of the artifact.
+    pub path: String,
+    /// The name of the declared type of the artifact.
+    pub artifact_type: Option<String>,
+    /// The flags of the artifact.
+    pub flags: OutputFlags,
+}
+
+/// The flags of an artifact produced by a tool.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct OutputFlags {
+    /// If set, the artifact must not be empty after it was produced.
+    pub nonempty: bool,
+    /// If set, the artifact is removed once all instances requiring it have finished.
+    pub temp: bool,
+    /// If set, the artifact is made read-only after it was produced, and is not overwritten unless forced.
+    pub protected: bool,
+}
+
+impl TryFrom<InterpreterValue> for Input {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        match value {
+            InterpreterValue::String(path) => Ok(Self {
+                path,
+                artifact_type: None,
+            }),
+            InterpreterValue::Dict(dict) => {
+                let mut path = None;
+                let mut artifact_type = None;
+                for (key, value) in dict {
+                    match key.as_str() {
+                        "path" => path = Some(String::try_from(value)?),
+                        "type" => artifact_type = Some(String::try_from(value)?),
+                        other => return Err(format!("unknown input attribute {:?}", other)),
+                    }
+                }
+                Ok(Self {
+                    path: path.ok_or_else(|| "input is missing its path".to_owned())?,
+                    artifact_type,
+                })
+            }
+            other => Err(format!(
+                "expected a string or a dict describing an input, but got a {}",
+                other.type_name()
+            )),
+        }
+    }
+}
+
+/// A single input is interpreted as a list containing only that input.
+impl TryFrom<InterpreterValue> for Vec<Input> {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        match value {
+            InterpreterValue::List(list) => list.into_iter().map(Input::try_from).collect(),
+            other => Ok(vec![Input::try_from(other)?]),
+        }
+    }
+}
+
+impl TryFrom<InterpreterValue> for Output {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        match value {
+            InterpreterValue::String(path) => Ok(Self {
+                path,
+                artifact_type: None,
+                flags: Default::default(),
+            }),
+            InterpreterValue::Dict(dict) => {
+                let mut path = None;
+                let mut artifact_type = None;
+                let mut flags = OutputFlags::default();
+                for (key, value) in dict {
+                    match key.as_str() {
+                        "path" => path = Some(String::try_from(value)?),
+                        "type" => artifact_type = Some(String::try_from(value)?),
+                        "nonempty" => flags.nonempty = bool::try_from(value)?,
+                        "temp" => flags.temp = bool::try_from(value)?,
+                        "protected" => flags.protected = bool::try_from(value)?,
+                        other => return Err(format!("unknown output flag {:?}", other)),
+                    }
+                }
+                Ok(Self {
+                    path: path.ok_or_else(|| "output is missing its path".to_owned())?,
+                    artifact_type,
+                    flags,
+                })
+            }
+            other => Err(format!(
+                "expected a string or a dict describing an output, but got a {}",
+                other.type_name()
+            )),
+        }
+    }
+}
+
+/// A single output is interpreted as a list containing only that output.
+impl TryFrom<InterpreterValue> for Vec<Output> {
+    type Error = String;
+
+    fn try_from(value: InterpreterValue) -> Result<Self, Self::Error> {
+        match value {
+            InterpreterValue::List(list) => list.into_iter().map(Output::try_from).collect(),
+            other => Ok(vec![Output::try_from(other)?]),
+        }
+    }
+}
+
+/// The resources requested by a tool instance.
+#[derive(PartialEq, Debug, Clone, Default, Serialize)]
+pub struct Resources {
+    /// The number of threads.
+    pub threads: Option<u64>,
+    /// The amount of memory in megabytes.
+    pub memory: Option<u64>,
+    /// The time in seconds after which the instance is killed.
+    pub timeout: Option<f64>,
+}
+
+/// Describes how a failed tool instance is retried.
+#[derive(PartialEq, Debug, Clone)]
+pub struct RetryPolicy {
+    /// The number of times a failed instance is executed again.
+    pub retries: u64,
+    /// The delay in seconds before the first retry, which is doubled for each further retry.
+    pub backoff: f64,
+    /// The factor by which memory and timeout are multiplied for each retry.
+    pub scaling: f64,
+}
+
+impl RetryPolicy {
+    /// The longest delay before a retry, at which the doubling backoff saturates.
+    pub const MAXIMUM_BACKOFF: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Returns the delay before starting the given attempt, starting at one.
+    pub fn backoff_before_attempt(&self, attempt: u64) -> Duration {
+        if attempt <= 1 {
+            Duration::from_secs(0)
+        } else {
+            let doublings = attempt.saturating_sub(2).min(64) as i32;
+            Duration::try_from_secs_f64(self.backoff.max(0.0) * 2f64.powi(doublings))
+                .map_or(Self::MAXIMUM_BACKOFF, |backoff| {
+                    backoff.min(Self::MAXIMUM_BACKOFF)
+                })
+        }
+    }
+}
+
+/// The graph implementation used for workflow graphs by default.
+pub type DefaultWorkflowGraphImplementation = DiGraph<ToolInstance, (), usize>;
+
+/// A directed acyclic graph of tool instances.
+/// An edge from one instance to another indicates that the second instance requires an artifact produced by the first.
+#[derive(Debug, Clone)]
+pub struct WorkflowGraph<
+    Graph: GraphBase<NodeData = ToolInstance> = DefaultWorkflowGraphImplementation,
+> {
+    /// The graph storing the tool instances.
     graph: Graph,
-    output_node_map: BTreeMap<ToolOutput, Graph::NodeIndex>,
-}*/
\ No newline at end of file
+    /// Maps the names of tool instances to their nodes.
+    instance_node_map: BTreeMap<String, Graph::NodeIndex>,
+    /// Maps each artifact to the node producing it.
+    output_node_map: BTreeMap<String, Graph::NodeIndex>,
+    /// Maps each artifact to the nodes requiring it.
+    input_node_map: BTreeMap<String, Vec<Graph::NodeIndex>>,
+}
+
+impl<Graph: Default + GraphBase<NodeData = ToolInstance>> Default for WorkflowGraph<Graph> {
+    fn default() -> Self {
+        Self {
+            graph: Default::default(),
+            instance_node_map: Default::default(),
+            output_node_map: Default::default(),
+            input_node_map: Default::default(),
+        }
+    }
+}
+
+impl<Graph: Default + DynamicGraph<NodeData = ToolInstance, EdgeData = ()>> WorkflowGraph<Graph> {
+    /// Adds a tool instance to the graph and connects it with the instances producing its inputs and requiring its outputs.
+    /// Returns an error if an output of the instance is already produced by a different instance.
+    pub fn add_tool_instance(
+        &mut self,
+        tool_instance: ToolInstance,
+    ) -> TypemakeResult<Graph::NodeIndex> {
+        for output in &tool_instance.outputs {
+            if let Some(&producer) = self.output_node_map.get(output) {
+                return Err(TypemakeError::GeneralError(format!(
+                    "Artifact {:?} is produced by both {:?} and {:?}",
+                    output,
+                    self.graph.node_data(producer).name,
+                    tool_instance.name
+                )));
+            }
+        }
+
+        let name = tool_instance.name.clone();
+        let inputs = tool_instance.inputs.clone();
+        let outputs = tool_instance.outputs.clone();
+        let node = self.graph.add_node(tool_instance);
+        self.instance_node_map.insert(name, node);
+
+        for input in inputs {
+            if let Some(&producer) = self.output_node_map.get(&input) {
+                self.add_dependency(producer, node);
+            }
+            self.input_node_map.entry(input).or_default().push(node);
+        }
+
+        for output in outputs {
+            for consumer in self
+                .input_node_map
+                .get(&output)
+                .cloned()
+                .unwrap_or_default()
+            {
+                self.add_dependency(node, consumer);
+            }
+            self.output_node_map.insert(output, node);
+        }
+
+        Ok(node)
+    }
+
+    /// Adds an edge from `producer` to `consumer`, if it does not exist yet.
+    fn add_dependency(&mut self, producer: Graph::NodeIndex, consumer: Graph::NodeIndex) {
+        if !self.graph.contains_edge_between(producer, consumer) {
+            self.graph.add_edge(producer, consumer, ());
+        }
+    }
+
+    /// Returns the tool instance stored at the given node.
+    pub fn tool_instance(&self, node: Graph::NodeIndex) -> &ToolInstance {
+        self.graph.node_data(node)
+    }
+
+    /// Returns the node of the tool instance with the given name.
+    pub fn instance_node(&self, name: &str) -> Option<Graph::NodeIndex> {
+        self.instance_node_map.get(name).copied()
+    }
+
+    /// Returns the node producing the given artifact.
+    pub fn producer(&self, artifact: &str) -> Option<Graph::NodeIndex> {
+        self.output_node_map.get(artifact).copied()
+    }
+
+    /// Returns the nodes whose instances require the given artifact.
+    pub fn consumers(&self, artifact: &str) -> Vec<Graph::NodeIndex> {
+        self.input_node_map
+            .get(artifact)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the nodes whose instances produce inputs of the given node.
+    pub fn predecessors(&self, node: Graph::NodeIndex) -> Vec<Graph::NodeIndex> {
+        self.graph
+            .in_neighbors(node)
+            .map(|neighbor| neighbor.node_id)
+            .collect()
+    }
+
+    /// Returns the nodes whose instances require outputs of the given node.
+    pub fn successors(&self, node: Graph::NodeIndex) -> Vec<Graph::NodeIndex> {
+        self.graph
+            .out_neighbors(node)
+            .map(|neighbor| neighbor.node_id)
+            .collect()
+    }
+
+    /// Returns the type errors of the graph, i.e. artifacts whose declared type is unknown,
+    /// and artifacts whose type declared by the consuming instance differs from the type declared by the producing instance.
+    /// Artifacts without declared type match any type.
+    pub fn type_errors(&self, types: &BTreeMap<String, ArtifactType>) -> Vec<String> {
+        let mut errors = Vec::new();
+        for node in self.nodes() {
+            let tool_instance = self.tool_instance(node);
+            for (artifact, artifact_type) in tool_instance
+                .input_types
+                .iter()
+                .chain(&tool_instance.output_types)
+            {
+                if !types.contains_key(artifact_type) {
+                    errors.push(format!(
+                        "Tool instance {:?} declares artifact {:?} with unknown type {:?}",
+                        tool_instance.name, artifact, artifact_type
+                    ));
+                }
+            }
+
+            for (input, input_type) in &tool_instance.input_types {
+                let producer = if let Some(producer) = self.producer(input) {
+                    self.tool_instance(producer)
+                } else {
+                    continue;
+                };
+                if let Some(output_type) = producer.output_types.get(input) {
+                    if output_type != input_type {
+                        errors.push(format!(
+                            "Tool instance {:?} produces artifact {:?} of type {:?}, but tool instance {:?} requires it to be of type {:?}",
+                            producer.name, input, output_type, tool_instance.name, input_type
+                        ));
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    /// Returns the nodes of all tool instances in the graph.
+    pub fn nodes(&self) -> Vec<Graph::NodeIndex> {
+        self.graph.node_indices().collect()
+    }
+
+    /// Returns the number of tool instances in the graph.
+    pub fn len(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// Returns true if the graph contains no tool instances.
+    pub fn is_empty(&self) -> bool {
+        self.graph.node_count() == 0
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::typemake;
+use std::fs::write;
+use tempfile::tempdir;
+
+#[test]
+fn checkpoint() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+def cluster_files():
+    with open('clusters.txt') as clusters:
+        return ['cluster_{}.txt'.format(i) for i in range(int(clusters.read()))]
+
+tool clusters:
+  checkpoint
+  output: 'clusters.txt'
+  interpreter: 'echo 3 > clusters.txt'
+
+tool split:
+  input: 'clusters.txt'
+  output: cluster_files()
+  interpreter: ' && '.join('touch ' + file for file in cluster_files())
+",
+    )
+    .unwrap();
+
+    assert!(typemake(directory.path(), &[]).status.success());
+
+    for i in 0..3 {
+        assert!(directory.path().join(format!("cluster_{}.txt", i)).exists());
+    }
+    assert!(!directory.path().join("cluster_3.txt").exists());
+}
+
+#[test]
+fn unresolvable_tool_without_checkpoint() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool clusters:
+  output: 'clusters.txt'
+  interpreter: 'echo 3 > clusters.txt'
+
+tool split:
+  input: 'clusters.txt'
+  output: open('clusters.txt').read().split()
+  interpreter: 'true'
+",
+    )
+    .unwrap();
+
+    assert!(!typemake(directory.path(), &[]).status.success());
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::typemake;
+use std::fs::write;
+use tempfile::tempdir;
+
+const TYPEFILE: &str = "
+tool first:
+  output: ['a.txt', temp('b.txt')]
+  interpreter: 'touch a.txt b.txt'
+
+tool second:
+  output: [protected('c.txt'), 'd.txt']
+  interpreter: 'touch c.txt d.txt'
+";
+
+#[test]
+fn clean() {
+    let directory = tempdir().unwrap();
+    let path = |file: &str| directory.path().join(file);
+    write(path("Typefile"), TYPEFILE).unwrap();
+    // Keep the temporary output by requesting it explicitly.
+    assert!(
+        typemake(directory.path(), &["a.txt", "b.txt", "c.txt", "d.txt"])
+            .status
+            .success()
+    );
+    for file in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+        assert!(path(file).exists());
+    }
+
+    // Without --yes, nothing is removed.
+    let output = typemake(directory.path(), &["clean", "--tool", "second", "--temp"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Would remove:"));
+    assert!(stdout.contains("\"b.txt\" (temporary output of tool instance \"first\")"));
+    assert!(stdout.contains("\"d.txt\" (output of tool instance \"second\")"));
+    assert!(stdout.contains("Skipping protected output of tool instance \"second\" \"c.txt\""));
+    assert!(path("b.txt").exists());
+    assert!(path("d.txt").exists());
+
+    let output = typemake(
+        directory.path(),
+        &["clean", "--tool", "second", "--temp", "--logs", "--yes"],
+    );
+    assert!(output.status.success());
+    assert!(path("a.txt").exists());
+    assert!(!path("b.txt").exists());
+    assert!(path("c.txt").exists());
+    assert!(!path("d.txt").exists());
+    assert!(!path(".typemake/logs/second.log").exists());
+    assert!(path(".typemake/metadata/second.json").exists());
+
+    let output = typemake(directory.path(), &["clean", "c.txt", "--force", "--yes"]);
+    assert!(output.status.success());
+    assert!(!path("c.txt").exists());
+}
+
+#[test]
+fn clean_orphaned() {
+    let directory = tempdir().unwrap();
+    let path = |file: &str| directory.path().join(file);
+    write(path("Typefile"), TYPEFILE).unwrap();
+    assert!(typemake(directory.path(), &[]).status.success());
+
+    // Remove the second tool and an output of the first tool from the typefile.
+    write(
+        path("Typefile"),
+        "
+tool first:
+  output: 'b.txt'
+  interpreter: 'touch b.txt'
+",
+    )
+    .unwrap();
+    let output = typemake(directory.path(), &["clean", "--orphaned", "--yes"]);
+    assert!(output.status.success());
+    for file in [
+        "a.txt",
+        "c.txt",
+        "d.txt",
+        ".typemake/logs/second.log",
+        ".typemake/metadata/second.json",
+    ] {
+        assert!(!path(file).exists(), "{} was not removed", file);
+    }
+    assert!(path(".typemake/metadata/first.json").exists());
+}
+
+#[test]
+fn clean_orphaned_keeps_uninstantiated_tools() {
+    let directory = tempdir().unwrap();
+    let path = |file: &str| directory.path().join(file);
+    write(
+        path("Typefile"),
+        "
+def cluster_files():
+    with open('clusters.txt') as clusters:
+        return ['cluster_{}.txt'.format(i) for i in range(int(clusters.read()))]
+
+tool clusters:
+  checkpoint
+  output: 'clusters.txt'
+  interpreter: 'echo 2 > clusters.txt'
+
+tool split:
+  input: 'clusters.txt'
+  output: cluster_files()
+  interpreter: ' && '.join('touch ' + file for file in cluster_files())
+",
+    )
+    .unwrap();
+    assert!(typemake(directory.path(), &[]).status.success());
+
+    // Without the checkpoint output, the second tool cannot be instantiated, but it is still part of the workflow.
+    assert!(
+        typemake(directory.path(), &["clean", "clusters.txt", "--yes"])
+            .status
+            .success()
+    );
+    let output = typemake(directory.path(), &["clean", "--orphaned", "--yes"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Nothing to clean"));
+    for file in [
+        "cluster_0.txt",
+        "cluster_1.txt",
+        ".typemake/logs/split.log",
+        ".typemake/metadata/split.json",
+    ] {
+        assert!(path(file).exists(), "{} was removed", file);
+    }
+}
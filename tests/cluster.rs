@@ -0,0 +1,216 @@
+mod common;
+
+use common::{typemake, typemake_command};
+use std::fs::{read_to_string, set_permissions, write, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+/// A stand-in for `sbatch --parsable`, running the job script in the background in its own process group.
+const FAKE_SBATCH: &str = r#"#!/bin/bash
+echo "$@" >> submissions.txt
+id=$(( $(cat next-id 2>/dev/null || echo 1000) + 1 ))
+echo $id > next-id
+mkdir -p jobs
+setsid bash -c "bash '${@: -1}'; echo \$? > jobs/$id" > /dev/null 2>&1 &
+echo $! > jobs/$id.pid
+echo "$id;fake-cluster"
+"#;
+
+/// A stand-in for a status command, reporting the exit status recorded by `FAKE_SBATCH`.
+const FAKE_STATUS: &str = r#"#!/bin/bash
+if test -e jobs/$1; then
+  if test "$(cat jobs/$1)" = 0; then echo success; else echo failed; fi
+else
+  echo running
+fi
+"#;
+
+/// A stand-in for `scancel`, killing the process group of the job.
+const FAKE_SCANCEL: &str = r#"#!/bin/bash
+echo $1 >> cancelled.txt
+kill -- -$(cat jobs/$1.pid)
+"#;
+
+const TYPEFILE: &str = "
+tool first:
+  output: 'first.txt'
+  threads: 2
+  interpreter: 'echo first on $TYPEMAKE_THREADS threads; echo $TYPEMAKE_ATTEMPT > first.txt'
+
+tool second:
+  input: 'first.txt'
+  output: 'second.txt'
+  interpreter: 'cat first.txt > second.txt; echo second >> second.txt'
+
+tool failing:
+  output: 'failing.txt'
+  interpreter: 'echo failing; exit 3'
+
+tool slow:
+  output: 'slow.txt'
+  interpreter: 'touch started; sleep 30; touch slow.txt'
+";
+
+fn write_cluster(directory: &Path) {
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+    for (name, content) in [
+        ("fake-sbatch", FAKE_SBATCH),
+        ("fake-status", FAKE_STATUS),
+        ("fake-scancel", FAKE_SCANCEL),
+    ] {
+        write(directory.join(name), content).unwrap();
+        set_permissions(directory.join(name), Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+#[test]
+fn cluster_executor() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write_cluster(directory);
+
+    let output = typemake(
+        directory,
+        &[
+            "--executor",
+            "cluster",
+            "--submit-command",
+            "./fake-sbatch --parsable --cpus-per-task {threads} --job-name {name}-{attempt}",
+            "--status-command",
+            "./fake-status",
+            "--resources",
+            "threads=1",
+            "second.txt",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("second.txt")).unwrap(),
+        "1\nsecond\n"
+    );
+    let submissions = read_to_string(directory.join("submissions.txt")).unwrap();
+    let submissions: Vec<_> = submissions.lines().collect();
+    assert_eq!(submissions.len(), 2);
+    assert!(submissions[0].starts_with("--parsable --cpus-per-task 2 --job-name first-1 "));
+    assert!(submissions[1].starts_with("--parsable --cpus-per-task 1 --job-name second-1 "));
+    assert!(read_to_string(directory.join(".typemake/logs/first.log"))
+        .unwrap()
+        .contains("first on 2 threads"));
+
+    let output = typemake(
+        directory,
+        &[
+            "--executor",
+            "cluster",
+            "--submit-command",
+            "./fake-sbatch",
+            "--status-command",
+            "./fake-status",
+            "failing.txt",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Tool instance \"failing\" failed with exit status: 3"));
+    assert!(read_to_string(directory.join(".typemake/logs/failing.log"))
+        .unwrap()
+        .contains("failing"));
+}
+
+#[test]
+fn cluster_executor_without_status_command() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write_cluster(directory);
+
+    // Without a status command, the exit status written by the job script is used.
+    let output = typemake(
+        directory,
+        &[
+            "--executor",
+            "cluster",
+            "--submit-command",
+            "./fake-sbatch",
+            "first.txt",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(read_to_string(directory.join("first.txt")).unwrap(), "1\n");
+
+    let output = typemake(
+        directory,
+        &[
+            "--executor",
+            "cluster",
+            "--submit-command",
+            "./fake-sbatch",
+            "failing.txt",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Tool instance \"failing\" failed with exit status: 3"));
+
+    let output = typemake(directory, &["--executor", "cluster", "first.txt"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("The cluster executor requires --submit-command"));
+
+    let output = typemake(
+        directory,
+        &[
+            "--executor",
+            "cluster",
+            "--submit-command",
+            "./fake-sbatch --mem {memory}",
+            "--force",
+            "first.txt",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("The submit command uses {memory}, but tool instance \"first\" does not request this resource"));
+}
+
+#[test]
+fn cluster_executor_cancels_jobs_on_interrupt() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write_cluster(directory);
+
+    let mut typemake = typemake_command(directory)
+        .args([
+            "--executor",
+            "cluster",
+            "--submit-command",
+            "./fake-sbatch",
+            "--status-command",
+            "./fake-status",
+            "--cancel-command",
+            "./fake-scancel",
+            "slow.txt",
+        ])
+        .spawn()
+        .unwrap();
+    let start = Instant::now();
+    while !directory.join(".typemake/staging/slow/started").exists() {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    unsafe { libc::kill(typemake.id() as libc::pid_t, libc::SIGINT) };
+    assert!(!typemake.wait().unwrap().success());
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(
+        read_to_string(directory.join("cancelled.txt")).unwrap(),
+        "1001\n"
+    );
+    let metadata = read_to_string(directory.join(".typemake/metadata/slow.json")).unwrap();
+    assert!(metadata.contains("\"incomplete\": true"));
+}
@@ -0,0 +1,27 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use assert_cmd::cargo::CommandCargoExt;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Returns a command running typemake in the given directory.
+/// The `bin` directory inside it is put in front of the `PATH`, such that tests can replace external tools with stand-ins.
+pub fn typemake_command(directory: &Path) -> Command {
+    let path = format!(
+        "{}:{}",
+        directory.join("bin").display(),
+        std::env::var("PATH").unwrap()
+    );
+    let mut typemake = Command::cargo_bin("typemake").expect("Could not find and compile typemake");
+    typemake.current_dir(directory).env("PATH", path);
+    typemake
+}
+
+/// Runs typemake in the given directory with the given arguments.
+pub fn typemake(directory: &Path, arguments: &[&str]) -> Output {
+    typemake_command(directory)
+        .args(arguments)
+        .output()
+        .unwrap()
+}
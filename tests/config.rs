@@ -0,0 +1,120 @@
+mod common;
+
+use common::typemake;
+use std::fs::{read_to_string, write};
+use tempfile::tempdir;
+
+const TYPEFILE: &str = "
+assert isinstance(config['repetitions'], int)
+
+tool greet:
+  output: config['dataset']['name'] + '.txt'
+  interpreter: 'echo ' + ' '.join([config['greeting']] * config['repetitions']) + ' > ' + config['dataset']['name'] + '.txt'
+";
+
+#[test]
+fn config() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+    write(
+        directory.join("config.yaml"),
+        "greeting: hello\nrepetitions: 1\ndataset:\n  name: small\n  size: 10\n",
+    )
+    .unwrap();
+    write(directory.join("config.toml"), "repetitions = 2\n").unwrap();
+    write(
+        directory.join("config.json"),
+        "{\"dataset\": {\"size\": 20}}",
+    )
+    .unwrap();
+
+    let output = typemake(
+        directory,
+        &[
+            "--configfile",
+            "config.yaml",
+            "--configfile",
+            "config.toml",
+            "--configfile",
+            "config.json",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("small.txt")).unwrap(),
+        "hello hello\n"
+    );
+
+    // Values that do not affect the evaluated properties do not cause a rerun.
+    let output = typemake(
+        directory,
+        &[
+            "--configfile",
+            "config.yaml",
+            "--configfile",
+            "config.toml",
+            "--config",
+            "dataset.size=30",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Tool instance \"greet\" is up to date"));
+
+    let output = typemake(
+        directory,
+        &["--configfile", "config.yaml", "--config", "repetitions=3"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("small.txt")).unwrap(),
+        "hello hello hello\n"
+    );
+
+    let output = typemake(
+        directory,
+        &[
+            "--configfile",
+            "config.yaml",
+            "--config",
+            "greeting=hi",
+            "status",
+        ],
+    );
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("its script changed"));
+
+    let output = typemake(
+        directory,
+        &[
+            "--configfile",
+            "config.yaml",
+            "--config",
+            "dataset.name=large",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("large.txt")).unwrap(),
+        "hello\n"
+    );
+
+    let output = typemake(
+        directory,
+        &["--configfile", "config.yaml", "--config", "greeting"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("expected an override of the form key=value"));
+
+    write(directory.join("config.ini"), "").unwrap();
+    let output = typemake(directory, &["--configfile", "config.ini"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown format"));
+}
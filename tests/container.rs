@@ -0,0 +1,236 @@
+mod common;
+
+use common::typemake;
+use std::fs::{create_dir_all, read_dir, read_to_string, set_permissions, write, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// A stand-in for docker and apptainer, recording its arguments in `runtime.log` and executing the command on the host.
+const FAKE_RUNTIME: &str = r#"#!/bin/bash
+echo "$(basename $0) $@" >> "$(dirname "$0")/../runtime.log"
+if test "$1" = image; then
+  echo sha256:0123abcd
+  exit 0
+fi
+shift
+while test $# -gt 0; do
+  case $1 in
+    --rm) shift ;;
+    --workdir|--pwd) cd "$2"; shift 2 ;;
+    --user|--volume|--bind|--env) shift 2 ;;
+    *) break ;;
+  esac
+done
+shift
+exec "$@"
+"#;
+
+/// A stand-in for conda, creating an environment with a `greet` executable.
+const FAKE_CONDA: &str = r#"#!/bin/bash
+case $1 in
+  env)
+    mkdir -p "$4/bin"
+    printf '#!/bin/bash\necho hello from conda\n' > "$4/bin/greet"
+    chmod +x "$4/bin/greet"
+    ;;
+  shell.posix)
+    echo "export PATH='$3/bin':\"\$PATH\""
+    ;;
+esac
+"#;
+
+const TYPEFILE: &str = "
+tool convert:
+  input: 'input.txt'
+  output: 'converted.txt'
+  container: config.get('image', 'alpine:3.18')
+  interpreter: 'tr a-z A-Z < input.txt > converted.txt'
+
+tool host:
+  output: 'host.txt'
+  interpreter: 'echo host > host.txt'
+
+tool managed:
+  output: 'managed.txt'
+  container: 'alpine:3.18'
+  conda: 'environment.yaml'
+  interpreter: 'greet > managed.txt'
+";
+
+fn write_container(directory: &Path) {
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+    write(directory.join("input.txt"), "hello\n").unwrap();
+    write(directory.join("environment.yaml"), "name: managed\n").unwrap();
+    create_dir_all(directory.join("bin")).unwrap();
+    for (name, content) in [
+        ("docker", FAKE_RUNTIME),
+        ("apptainer", FAKE_RUNTIME),
+        ("conda", FAKE_CONDA),
+    ] {
+        write(directory.join("bin").join(name), content).unwrap();
+        set_permissions(
+            directory.join("bin").join(name),
+            Permissions::from_mode(0o755),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn docker_container() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path().canonicalize().unwrap();
+    let directory = directory.as_path();
+    write_container(directory);
+
+    let output = typemake(directory, &["converted.txt", "host.txt"]);
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("converted.txt")).unwrap(),
+        "HELLO\n"
+    );
+    assert!(directory.join("host.txt").exists());
+
+    let runtime_log = read_to_string(directory.join("runtime.log")).unwrap();
+    let calls: Vec<_> = runtime_log.lines().collect();
+    assert_eq!(calls.len(), 2);
+    let staging = directory.join(".typemake/staging/convert");
+    assert!(calls[0].starts_with("docker run --rm --user "));
+    assert!(calls[0].contains(&format!("--workdir {}", staging.display())));
+    assert!(calls[0].contains(&format!(
+        "--volume {}:{} ",
+        staging.display(),
+        staging.display()
+    )));
+    assert!(calls[0].contains(&format!(
+        "--volume {}:{}:ro ",
+        directory.join("input.txt").display(),
+        directory.join("input.txt").display()
+    )));
+    assert!(calls[0].contains("--env TYPEMAKE_ATTEMPT "));
+    assert!(calls[0].ends_with(&format!(
+        "alpine:3.18 bash -c bash '{}/.typemake-script.sh'",
+        staging.display()
+    )));
+    assert_eq!(
+        calls[1],
+        "docker image inspect --format {{.Id}} alpine:3.18"
+    );
+
+    let metadata = read_to_string(directory.join(".typemake/metadata/convert.json")).unwrap();
+    assert!(metadata.contains("\"container_image\": \"alpine:3.18\""));
+    assert!(metadata.contains("\"container_digest\": \"sha256:0123abcd\""));
+    let metadata = read_to_string(directory.join(".typemake/metadata/host.json")).unwrap();
+    assert!(metadata.contains("\"container_image\": null"));
+
+    // Changing the image causes a rerun.
+    let output = typemake(
+        directory,
+        &["--config", "image=alpine:3.19", "converted.txt"],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Tool instance \"convert\" is outdated: its container image changed"));
+    let metadata = read_to_string(directory.join(".typemake/metadata/convert.json")).unwrap();
+    assert!(metadata.contains("\"container_image\": \"alpine:3.19\""));
+}
+
+#[test]
+fn apptainer_container() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path().canonicalize().unwrap();
+    let directory = directory.as_path();
+    write_container(directory);
+    write(directory.join("image.sif"), "fake image\n").unwrap();
+
+    let image = directory.join("image.sif");
+    let output = typemake(
+        directory,
+        &[
+            "--container-runtime",
+            "apptainer",
+            "--config",
+            &format!("image={}", image.display()),
+            "converted.txt",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("converted.txt")).unwrap(),
+        "HELLO\n"
+    );
+
+    let runtime_log = read_to_string(directory.join("runtime.log")).unwrap();
+    assert!(runtime_log.starts_with(&format!(
+        "apptainer exec --pwd {}",
+        directory.join(".typemake/staging/convert").display()
+    )));
+    assert!(runtime_log.contains(&format!(
+        "--bind {}:{}:ro ",
+        directory.join("input.txt").display(),
+        directory.join("input.txt").display()
+    )));
+    assert!(!runtime_log.contains("--env"));
+
+    // The digest of an image file is computed by typemake itself.
+    let metadata = read_to_string(directory.join(".typemake/metadata/convert.json")).unwrap();
+    assert!(metadata.contains(
+        "\"container_digest\": \"sha256:fb63c1efa50246f48bd9cce66f5b9af817f1d55c783ac8242e1547b3fc81fa86\""
+    ));
+}
+
+#[test]
+fn container_environment() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path().canonicalize().unwrap();
+    let directory = directory.as_path();
+    write_container(directory);
+
+    // The environment is created on the host and mounted read-only into the container.
+    let output = typemake(directory, &["managed.txt"]);
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("managed.txt")).unwrap(),
+        "hello from conda\n"
+    );
+    let runtime_log = read_to_string(directory.join("runtime.log")).unwrap();
+    let environment_directory = read_dir(directory.join(".typemake/environments"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.is_dir())
+        .unwrap();
+    assert!(runtime_log.contains(&format!(
+        "--volume {}:{}:ro ",
+        environment_directory.display(),
+        environment_directory.display()
+    )));
+}
+
+#[test]
+fn container_environment_outside_conda() {
+    for environment in ["venv: 'requirements.txt'", "nix: 'environment.nix'"] {
+        let directory = tempdir().unwrap();
+        let directory = directory.path();
+        write(
+            directory.join("Typefile"),
+            format!(
+                "
+tool contained:
+  container: 'ubuntu:22.04'
+  {}
+  interpreter: 'true'
+",
+                environment
+            ),
+        )
+        .unwrap();
+
+        let output = typemake(directory, &[]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("but only conda environments can be used inside containers"));
+    }
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use common::typemake;
+use std::fs::{create_dir_all, read_to_string, set_permissions, write, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// A stand-in for conda, creating an environment with a `greet` executable printing the first line of the environment file.
+/// Like the other stand-ins, it records its arguments in `environments.log`.
+const FAKE_CONDA: &str = r#"#!/bin/bash
+case $1 in
+  env)
+    echo "conda $@" >> "$(dirname "$0")/../environments.log"
+    if grep -q broken "$6"; then
+      echo "Could not solve the environment" >&2
+      exit 1
+    fi
+    mkdir -p "$4/bin"
+    printf '#!/bin/bash\necho "%s"\n' "$(head -n 1 "$6")" > "$4/bin/greet"
+    chmod +x "$4/bin/greet"
+    ;;
+  shell.posix)
+    echo "export CONDA_PREFIX='$3'; export PATH='$3/bin':\"\$PATH\""
+    ;;
+esac
+"#;
+
+/// A stand-in for `python3 -m venv`, creating an activation script and a `pip` recording its arguments.
+const FAKE_PYTHON: &str = r#"#!/bin/bash
+log="$(dirname "$0")/../environments.log"
+echo "python3 $@" >> "$log"
+mkdir -p "$3/bin"
+echo "export VIRTUAL_ENV='$3'" > "$3/bin/activate"
+printf '#!/bin/bash\necho "pip $@" >> "%s"\n' "$log" > "$3/bin/pip"
+chmod +x "$3/bin/pip"
+"#;
+
+/// A stand-in for `nix-build`, creating an output with a `hello` executable.
+const FAKE_NIX_BUILD: &str = r#"#!/bin/bash
+echo "nix-build $@" >> "$(dirname "$0")/../environments.log"
+mkdir -p "$3/bin"
+printf '#!/bin/bash\necho hello from nix\n' > "$3/bin/hello"
+chmod +x "$3/bin/hello"
+"#;
+
+const TYPEFILE: &str = "
+tool first:
+  output: 'first.txt'
+  conda: 'environment.yaml'
+  interpreter: 'greet > first.txt'
+
+tool second:
+  output: 'second.txt'
+  conda: 'environment.yaml'
+  interpreter: 'greet > second.txt; echo $CONDA_PREFIX >> second.txt'
+
+tool virtual:
+  output: 'virtual.txt'
+  venv: 'requirements.txt'
+  interpreter: 'echo $VIRTUAL_ENV > virtual.txt'
+
+tool pinned:
+  output: 'pinned.txt'
+  nix: 'default.nix'
+  interpreter: 'hello > pinned.txt'
+";
+
+fn write_environments(directory: &Path, typefile: &str) {
+    write(directory.join("Typefile"), typefile).unwrap();
+    write(directory.join("environment.yaml"), "name: first\n").unwrap();
+    write(directory.join("requirements.txt"), "numpy==1.26.4\n").unwrap();
+    write(
+        directory.join("default.nix"),
+        "{ pkgs ? import <nixpkgs> {} }: pkgs.hello\n",
+    )
+    .unwrap();
+    create_dir_all(directory.join("bin")).unwrap();
+    for (name, content) in [
+        ("conda", FAKE_CONDA),
+        ("python3", FAKE_PYTHON),
+        ("nix-build", FAKE_NIX_BUILD),
+    ] {
+        write(directory.join("bin").join(name), content).unwrap();
+        set_permissions(
+            directory.join("bin").join(name),
+            Permissions::from_mode(0o755),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn environments() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path().canonicalize().unwrap();
+    let directory = directory.as_path();
+    write_environments(directory, TYPEFILE);
+
+    let targets = ["first.txt", "second.txt", "virtual.txt", "pinned.txt"];
+    let output = typemake(directory, &targets);
+    assert!(output.status.success());
+    // Environments are created by the jobs of the instances using them.
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("[pinned] Creating nix environment "));
+    assert_eq!(
+        read_to_string(directory.join("first.txt")).unwrap(),
+        "name: first\n"
+    );
+    let environment_directory = directory.join(".typemake/environments");
+    let second = read_to_string(directory.join("second.txt")).unwrap();
+    let second: Vec<_> = second.lines().collect();
+    assert_eq!(second[0], "name: first");
+    assert!(second[1].starts_with(&format!("{}/conda-", environment_directory.display())));
+    assert!(read_to_string(directory.join("virtual.txt"))
+        .unwrap()
+        .starts_with(&format!("{}/venv-", environment_directory.display())));
+    assert_eq!(
+        read_to_string(directory.join("pinned.txt")).unwrap(),
+        "hello from nix\n"
+    );
+
+    // The conda environment is shared by both tools, so it is created only once.
+    let environment_log = read_to_string(directory.join("environments.log")).unwrap();
+    let creations: Vec<_> = environment_log.lines().collect();
+    assert_eq!(creations.len(), 4);
+    assert_eq!(
+        creations
+            .iter()
+            .filter(|line| line.starts_with("conda env create --prefix "))
+            .count(),
+        1
+    );
+    let requirements = format!(
+        "pip install --requirement {}",
+        directory.join("requirements.txt").display()
+    );
+    assert!(creations.contains(&requirements.as_str()));
+    let nix_build = format!(
+        "nix-build {} --out-link ",
+        directory.join("default.nix").display()
+    );
+    assert!(creations.iter().any(|line| line.starts_with(&nix_build)));
+    let metadata = read_to_string(directory.join(".typemake/metadata/first.json")).unwrap();
+    assert!(metadata.contains("\"environment_hash\": \""));
+
+    // Cached environments are reused, and instances with unchanged environments are up to date.
+    let output = typemake(directory, &targets);
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("environments.log")).unwrap(),
+        environment_log
+    );
+
+    // A changed specification results in a new environment and reruns the instances using it.
+    write(directory.join("environment.yaml"), "name: changed\n").unwrap();
+    let output = typemake(directory, &targets);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Tool instance \"first\" is outdated: its software environment changed")
+    );
+    assert!(stderr.contains("Tool instance \"virtual\" is up to date"));
+    assert_eq!(
+        read_to_string(directory.join("first.txt")).unwrap(),
+        "name: changed\n"
+    );
+    assert_eq!(
+        read_to_string(directory.join("environments.log"))
+            .unwrap()
+            .lines()
+            .filter(|line| line.starts_with("conda env create --prefix "))
+            .count(),
+        2
+    );
+
+    // A deleted specification makes the instances using it outdated, instead of failing the check.
+    std::fs::remove_file(directory.join("requirements.txt")).unwrap();
+    let output = typemake(directory, &targets);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(
+        "Tool instance \"virtual\" is outdated: its venv environment specification \"requirements.txt\" cannot be read"
+    ));
+    assert!(stderr.contains("Could not read the venv environment specification"));
+}
+
+#[test]
+fn environment_errors() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write_environments(
+        directory,
+        "
+tool both:
+  output: 'both.txt'
+  conda: 'environment.yaml'
+  venv: 'requirements.txt'
+  interpreter: 'touch both.txt'
+",
+    );
+    let output = typemake(directory, &["both.txt"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains(
+        "declares the environments conda, venv, but at most one environment is allowed per tool"
+    ));
+
+    write_environments(
+        directory,
+        "
+tool broken:
+  output: 'broken.txt'
+  conda: 'broken.yaml'
+  interpreter: 'touch broken.txt'
+",
+    );
+    write(directory.join("broken.yaml"), "name: broken\n").unwrap();
+    let output = typemake(directory, &["broken.txt"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Creating the conda environment from \"broken.yaml\" failed. See "));
+    assert!(stderr.contains("Tool instance \"broken\" failed with exit status: 1"));
+    let logs: Vec<_> = std::fs::read_dir(directory.join(".typemake/environments"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "log"))
+        .collect();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(
+        read_to_string(&logs[0]).unwrap(),
+        "Could not solve the environment\n"
+    );
+    assert!(!directory.join("broken.txt").exists());
+}
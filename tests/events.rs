@@ -0,0 +1,138 @@
+mod common;
+
+use common::typemake;
+use serde_json::Value;
+use std::fs::{read_to_string, write};
+use std::io::Read;
+use std::os::unix::net::UnixListener;
+use tempfile::tempdir;
+
+const TYPEFILE: &str = "
+tool first:
+  output: 'first.txt'
+  threads: 2
+  interpreter: 'touch first.txt'
+
+tool second:
+  input: 'first.txt'
+  output: 'second.txt'
+  retries: 1
+  interpreter: 'if test -e attempted; then touch second.txt; else touch ../../../attempted; exit 1; fi'
+";
+
+/// Parses the given newline-delimited JSON events.
+fn parse_events(events: &str) -> Vec<Value> {
+    events
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+/// Returns the kind of each event, followed by the instance it concerns, if any.
+fn event_kinds(events: &[Value]) -> Vec<String> {
+    events
+        .iter()
+        .map(|event| match event["instance"].as_str() {
+            Some(instance) => format!("{} {}", event["event"].as_str().unwrap(), instance),
+            None => event["event"].as_str().unwrap().to_owned(),
+        })
+        .collect()
+}
+
+#[test]
+fn events_to_file() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+
+    let output = typemake(
+        directory,
+        &["--events", "json", "--events-to", "events.json"],
+    );
+    assert!(output.status.success());
+    let events = parse_events(&read_to_string(directory.join("events.json")).unwrap());
+    assert_eq!(
+        event_kinds(&events),
+        [
+            "workflow_started",
+            "dag_built",
+            "job_queued first",
+            "job_started first",
+            "job_finished first",
+            "job_queued second",
+            "job_started second",
+            "job_failed second",
+            "job_started second",
+            "job_finished second",
+            "workflow_finished",
+        ]
+    );
+    assert!(events
+        .iter()
+        .all(|event| event["time"].as_f64().unwrap() > 0.0));
+    assert_eq!(events[1]["tool_instances"], 2);
+    assert_eq!(events[3]["resources"]["threads"], 2);
+    assert_eq!(events[7]["retrying"], true);
+    assert_eq!(events[8]["attempt"], 2);
+    assert!(events[9]["benchmark"]["wall_time"].as_f64().unwrap() >= 0.0);
+    assert_eq!(events[10]["success"], true);
+
+    // Events of further invocations are appended.
+    let output = typemake(
+        directory,
+        &["--events", "json", "--events-to", "events.json"],
+    );
+    assert!(output.status.success());
+    let events = parse_events(&read_to_string(directory.join("events.json")).unwrap());
+    assert_eq!(
+        event_kinds(&events[11..]),
+        ["workflow_started", "dag_built", "workflow_finished"]
+    );
+}
+
+#[test]
+fn events_to_socket() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(
+        directory.join("Typefile"),
+        "tool broken:\n  output: 'broken.txt'\n  interpreter: 'exit 1'\n",
+    )
+    .unwrap();
+
+    let listener = UnixListener::bind(directory.join("events.socket")).unwrap();
+    let receiver = std::thread::spawn(move || {
+        let mut events = String::new();
+        listener
+            .accept()
+            .unwrap()
+            .0
+            .read_to_string(&mut events)
+            .unwrap();
+        events
+    });
+
+    let output = typemake(
+        directory,
+        &["--events", "json", "--events-to", "events.socket"],
+    );
+    assert!(!output.status.success());
+    let events = parse_events(&receiver.join().unwrap());
+    assert_eq!(
+        event_kinds(&events),
+        [
+            "workflow_started",
+            "dag_built",
+            "job_queued broken",
+            "job_started broken",
+            "job_failed broken",
+            "workflow_finished",
+        ]
+    );
+    assert_eq!(events[4]["reason"], "failed with exit status: 1");
+    assert_eq!(events[5]["success"], false);
+    assert!(events[5]["error"]
+        .as_str()
+        .unwrap()
+        .contains("Tool instance \"broken\" failed"));
+}
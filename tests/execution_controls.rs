@@ -0,0 +1,74 @@
+mod common;
+
+use common::typemake;
+use std::fs::{read_to_string, write};
+use std::path::Path;
+use tempfile::tempdir;
+
+/// A pipeline of three tools, each appending a line to a counter file when executed.
+const TYPEFILE: &str = "
+tool first:
+  output: 'first.txt'
+  interpreter: 'echo first >> executions.txt && touch first.txt'
+
+tool second:
+  input: 'first.txt'
+  output: 'second.txt'
+  interpreter: 'echo second >> executions.txt && touch second.txt'
+
+tool third:
+  input: 'second.txt'
+  output: 'third.txt'
+  interpreter: 'echo third >> executions.txt && touch third.txt'
+";
+
+/// Returns the executions recorded since the last call, and resets the record.
+fn executions(directory: &Path) -> Vec<String> {
+    let path = directory.join("executions.txt");
+    let executions = read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_owned)
+        .collect();
+    let _ = std::fs::remove_file(path);
+    executions
+}
+
+#[test]
+fn execution_controls() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+    // The executions file is written through the staging directory, so it needs to exist beforehand.
+    write(directory.join("executions.txt"), "").unwrap();
+
+    assert!(typemake(directory, &["--until", "second"]).status.success());
+    assert_eq!(executions(directory), ["first", "second"]);
+    assert!(!directory.join("third.txt").exists());
+
+    write(directory.join("executions.txt"), "").unwrap();
+    assert!(typemake(directory, &[]).status.success());
+    assert_eq!(executions(directory), ["third"]);
+
+    write(directory.join("executions.txt"), "").unwrap();
+    assert!(typemake(directory, &["second.txt", "--force"])
+        .status
+        .success());
+    assert_eq!(executions(directory), ["second"]);
+
+    write(directory.join("executions.txt"), "").unwrap();
+    assert!(typemake(directory, &["--forcerun", "second"])
+        .status
+        .success());
+    assert_eq!(executions(directory), ["second", "third"]);
+
+    write(directory.join("executions.txt"), "").unwrap();
+    assert!(typemake(directory, &["--force"]).status.success());
+    assert_eq!(executions(directory), ["first", "second", "third"]);
+
+    let output = typemake(directory, &["--until", "fourth"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Unknown tool \"fourth\" given to --until"));
+}
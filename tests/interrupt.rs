@@ -0,0 +1,49 @@
+mod common;
+
+use common::{typemake, typemake_command};
+use std::fs::{read_to_string, write};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+#[test]
+fn interrupt_marks_outputs_incomplete() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool slow:
+  output: 'result.txt'
+  interpreter: 'if test -e second; then echo complete > result.txt; else echo partial > result.txt; touch started; sleep 30; fi'
+",
+    )
+    .unwrap();
+
+    let mut process = typemake_command(directory.path()).spawn().unwrap();
+    let start = Instant::now();
+    while !directory
+        .path()
+        .join(".typemake/staging/slow/started")
+        .exists()
+    {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    unsafe { libc::kill(process.id() as libc::pid_t, libc::SIGINT) };
+    assert!(!process.wait().unwrap().success());
+    assert!(start.elapsed() < Duration::from_secs(10));
+    let metadata = read_to_string(directory.path().join(".typemake/metadata/slow.json")).unwrap();
+    assert!(metadata.contains("\"incomplete\": true"));
+    // The partial output stays in the staging directory.
+    assert!(!directory.path().join("result.txt").exists());
+
+    // The instance was not finished, so it must be executed again.
+    write(directory.path().join("second"), "").unwrap();
+    assert!(typemake(directory.path(), &[]).status.success());
+    assert_eq!(
+        read_to_string(directory.path().join("result.txt")).unwrap(),
+        "complete\n"
+    );
+    let metadata = read_to_string(directory.path().join(".typemake/metadata/slow.json")).unwrap();
+    assert!(metadata.contains("\"incomplete\": false"));
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::typemake;
+use std::fs::write;
+use tempfile::tempdir;
+
+const TYPEFILE: &str = "
+tool broken:
+  output: 'broken.txt'
+  interpreter: 'echo something went wrong; exit 3'
+
+tool dependant:
+  input: 'broken.txt'
+  output: 'dependant.txt'
+  interpreter: 'touch dependant.txt'
+
+tool transitive_dependant:
+  input: 'dependant.txt'
+  output: 'transitive_dependant.txt'
+  interpreter: 'touch transitive_dependant.txt'
+
+tool independent:
+  output: 'independent.txt'
+  interpreter: 'touch independent.txt'
+";
+
+#[test]
+fn keep_going() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+
+    // Without --keep-going, the first failure aborts the workflow.
+    let output = typemake(directory, &[]);
+    assert!(!output.status.success());
+    assert!(!directory.join("independent.txt").exists());
+
+    let output = typemake(directory, &["--keep-going"]);
+    assert!(!output.status.success());
+    assert!(directory.join("independent.txt").exists());
+    assert!(!directory.join("dependant.txt").exists());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let row = stdout
+        .lines()
+        .find(|line| line.starts_with("broken "))
+        .unwrap();
+    assert!(row.contains(" 3  failed with exit status: 3"), "{}", row);
+    assert!(stdout.contains("    | something went wrong"));
+    assert!(stdout.contains("    skipped dependants: dependant, transitive_dependant"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Failed tool instances: \"broken\""));
+    assert!(!stderr.contains("could not be executed"));
+}
+
+#[test]
+fn keep_going_profile() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+    write(
+        directory.join("typemake.toml"),
+        "[profiles.default]\nkeep_going = true\n",
+    )
+    .unwrap();
+
+    // The command line overrides a profile enabling keep going.
+    let output = typemake(directory, &["--no-keep-going"]);
+    assert!(!output.status.success());
+    assert!(!directory.join("independent.txt").exists());
+
+    let output = typemake(directory, &[]);
+    assert!(!output.status.success());
+    assert!(directory.join("independent.txt").exists());
+
+    let output = typemake(directory, &["--keep-going", "--no-keep-going"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("cannot be used with"));
+}
+
+#[test]
+fn keep_going_without_script() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(
+        directory.join("Typefile"),
+        "
+tool declared:
+  output: 'declared.txt'
+",
+    )
+    .unwrap();
+
+    let output = typemake(directory, &["--keep-going"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let row = stdout
+        .lines()
+        .find(|line| line.starts_with("declared "))
+        .unwrap();
+    // The instance has no process, and thus no exit code.
+    assert!(
+        row.contains(" -  did not produce the declared outputs"),
+        "{}",
+        row
+    );
+}
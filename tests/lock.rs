@@ -0,0 +1,109 @@
+mod common;
+
+use common::typemake_command;
+use std::fs::{create_dir_all, read_dir, write};
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+    assert_eq!(
+        unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) },
+        0
+    );
+    let length = buffer.iter().position(|&byte| byte == 0).unwrap();
+    String::from_utf8(buffer[..length].to_vec()).unwrap()
+}
+
+fn write_lock(directory: &Path, pid: u32, artifacts: &[&str]) {
+    let lock_directory = directory.join(".typemake/locks");
+    create_dir_all(&lock_directory).unwrap();
+    write(
+        lock_directory.join(format!("other-{}.lock", pid)),
+        serde_json::json!({"pid": pid, "host": hostname(), "artifacts": artifacts}).to_string(),
+    )
+    .unwrap();
+}
+
+fn lock_count(directory: &Path) -> usize {
+    read_dir(directory.join(".typemake/locks"))
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("lock".as_ref()))
+        .count()
+}
+
+#[test]
+fn lock() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool first:
+  output: 'first.txt'
+  interpreter: 'echo first > first.txt'
+
+tool second:
+  output: 'second.txt'
+  interpreter: 'echo second > second.txt'
+",
+    )
+    .unwrap();
+
+    // A running process holds a lock on one of the outputs.
+    write_lock(directory.path(), std::process::id(), &["second.txt"]);
+    let output = typemake_command(directory.path()).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("typemake unlock"));
+    assert!(!directory.path().join("first.txt").exists());
+    assert_eq!(lock_count(directory.path()), 1);
+
+    // Targets that do not overlap with the lock can be produced concurrently.
+    let output = typemake_command(directory.path())
+        .arg("first.txt")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(directory.path().join("first.txt").exists());
+    assert_eq!(lock_count(directory.path()), 1);
+
+    // The lock of a running process is only removed when forced.
+    let output = typemake_command(directory.path())
+        .arg("unlock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(lock_count(directory.path()), 1);
+    let output = typemake_command(directory.path())
+        .arg("unlock")
+        .arg("--force")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(lock_count(directory.path()), 0);
+
+    let output = typemake_command(directory.path()).output().unwrap();
+    assert!(output.status.success());
+    assert!(directory.path().join("second.txt").exists());
+    assert_eq!(lock_count(directory.path()), 0);
+}
+
+#[test]
+fn unlock_stale() {
+    let directory = tempdir().unwrap();
+    let mut child = Command::new("true").spawn().unwrap();
+    child.wait().unwrap();
+    write_lock(directory.path(), child.id(), &["output.txt"]);
+
+    let output = typemake_command(directory.path())
+        .arg("unlock")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Removed 1 locks"));
+    assert_eq!(lock_count(directory.path()), 0);
+}
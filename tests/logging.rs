@@ -0,0 +1,51 @@
+mod common;
+
+use common::typemake;
+use std::fs::{read_to_string, write};
+use tempfile::tempdir;
+
+#[test]
+fn logging() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(
+        directory.join("Typefile"),
+        "tool greet:\n  output: 'greeting.txt'\n  interpreter: 'echo hello | tee greeting.txt'\n",
+    )
+    .unwrap();
+
+    // Logs go to stderr, and debug messages are hidden by default.
+    let output = typemake(directory, &["--forcerun", "greet"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Executing tool instance \"greet\""));
+    assert!(stderr.contains("[greet] hello"));
+    assert!(!stderr.contains("Publishing output"));
+    assert!(output.stdout.is_empty());
+
+    let output = typemake(directory, &["--forcerun", "greet", "-v"]);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Publishing output \"greeting.txt\""));
+
+    // The log file receives a full trace regardless of the verbosity of the terminal.
+    let output = typemake(
+        directory,
+        &["--forcerun", "greet", "-q", "--log-file", "typemake.log"],
+    );
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    let log = read_to_string(directory.join("typemake.log")).unwrap();
+    assert!(log.contains("Executing tool instance \"greet\""));
+    assert!(log.contains("Publishing output \"greeting.txt\""));
+
+    // Errors are shown even when quiet.
+    let output = typemake(directory, &["-q", "--until", "unknown"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Unknown tool \"unknown\""));
+
+    let output = typemake(directory, &["-v", "-q"]);
+    assert!(!output.status.success());
+}
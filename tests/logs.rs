@@ -0,0 +1,44 @@
+mod common;
+
+use common::typemake;
+use std::fs::{read_to_string, write};
+use tempfile::tempdir;
+
+#[test]
+fn logs() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool greet:
+  interpreter: 'echo hello && echo world >&2'
+
+tool custom:
+  log: 'custom/custom.log'
+  interpreter: 'echo custom'
+",
+    )
+    .unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("[greet] hello"));
+    assert!(stderr.contains("[greet] world"));
+
+    let log = read_to_string(directory.path().join(".typemake/logs/greet.log")).unwrap();
+    assert!(log.contains("hello\n"));
+    assert!(log.contains("world\n"));
+    let log = read_to_string(directory.path().join("custom/custom.log")).unwrap();
+    assert_eq!(log, "custom\n");
+
+    for (instance, expected) in [("greet", "hello\n"), ("custom", "custom\n")] {
+        let output = typemake(directory.path(), &["logs", instance]);
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().contains(expected));
+    }
+
+    assert!(!typemake(directory.path(), &["logs", "missing"])
+        .status
+        .success());
+}
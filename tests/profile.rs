@@ -0,0 +1,120 @@
+mod common;
+
+use common::typemake_command;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use std::process::Output;
+use tempfile::tempdir;
+
+/// Runs typemake with the given user configuration directory.
+fn typemake(directory: &Path, user_config_directory: &Path, arguments: &[&str]) -> Output {
+    typemake_command(directory)
+        .env("XDG_CONFIG_HOME", user_config_directory)
+        .args(arguments)
+        .output()
+        .unwrap()
+}
+
+const TYPEFILE: &str = "
+tool first:
+  output: 'first.txt'
+  interpreter: 'echo ' + config.get('greeting', 'hello') + ' ${TYPEMAKE_THREADS:-} > first.txt'
+
+tool second:
+  output: 'second.txt'
+  threads: 1
+  interpreter: 'echo $TYPEMAKE_THREADS > second.txt'
+";
+
+const PROFILES: &str = "
+[profiles.default]
+targets = ['second.txt']
+
+[profiles.laptop]
+resources = { threads = 2 }
+targets = ['first.txt']
+config = { greeting = 'hi' }
+
+[profiles.cluster]
+executor = 'pbs'
+";
+
+#[test]
+fn profiles() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    let user_config_directory = tempdir().unwrap();
+    let user_config_directory = user_config_directory.path();
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+    write(directory.join("typemake.toml"), PROFILES).unwrap();
+
+    // The default profile is used if no profile is given.
+    let output = typemake(directory, user_config_directory, &[]);
+    assert!(output.status.success());
+    assert!(directory.join("second.txt").exists());
+    assert!(!directory.join("first.txt").exists());
+
+    let output = typemake(directory, user_config_directory, &["--profile", "laptop"]);
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("first.txt")).unwrap(),
+        "hi 2\n"
+    );
+    // Resources declared by the tool take precedence over those of the profile.
+    let output = typemake(
+        directory,
+        user_config_directory,
+        &["--profile", "laptop", "--forcerun", "second", "second.txt"],
+    );
+    assert!(output.status.success());
+    assert_eq!(read_to_string(directory.join("second.txt")).unwrap(), "1\n");
+
+    // Arguments take precedence over the profile.
+    let output = typemake(
+        directory,
+        user_config_directory,
+        &[
+            "--profile",
+            "laptop",
+            "--config",
+            "greeting=hey",
+            "--resources",
+            "threads=3",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("first.txt")).unwrap(),
+        "hey 3\n"
+    );
+
+    let output = typemake(directory, user_config_directory, &["--profile", "cluster"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Unknown executor \"pbs\""));
+
+    let output = typemake(directory, user_config_directory, &["--profile", "unknown"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown profile \"unknown\""));
+
+    // Profiles are also read from the user configuration directory.
+    create_dir_all(user_config_directory.join("typemake")).unwrap();
+    write(
+        user_config_directory.join("typemake/typemake.toml"),
+        "[profiles.user]\nconfig = { greeting = 'howdy' }\n",
+    )
+    .unwrap();
+    let output = typemake(
+        directory,
+        user_config_directory,
+        &["--profile", "user", "first.txt"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(directory.join("first.txt")).unwrap(),
+        "howdy\n"
+    );
+}
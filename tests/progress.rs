@@ -0,0 +1,47 @@
+mod common;
+
+use common::typemake;
+use std::fs::write;
+use tempfile::tempdir;
+
+const TYPEFILE: &str = "
+tool slow:
+  output: 'slow.txt'
+  threads: 2
+  interpreter: 'sleep 0.5 && touch slow.txt'
+
+tool fast:
+  input: 'slow.txt'
+  output: 'fast.txt'
+  interpreter: 'touch fast.txt'
+";
+
+#[test]
+fn progress_display() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+
+    let output = typemake(directory, &["--progress", "always"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("[>                             ] 0 of 2 tool instances done, 1 running")
+    );
+    // Local jobs show their current resource usage relative to the requested resources.
+    assert!(stderr.lines().any(|line| line.starts_with("  slow ")
+        && line.contains("s  cpu ")
+        && line.contains(" of 2  mem ")
+        && line.ends_with(" MB")));
+    // The display is removed by moving the cursor up and clearing the rest of the terminal.
+    assert!(stderr.contains("\x1b[J"));
+
+    // Without a terminal, only plain log lines are written.
+    let output = typemake(directory, &["--forcerun", "slow"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains("tool instances done, "));
+    assert!(!stderr.contains("\x1b[J"));
+    assert!(stderr.contains("Finished tool instance \"slow\" (1 of 2 tool instances done)"));
+    assert!(stderr.contains("Finished tool instance \"fast\" (2 of 2 tool instances done)"));
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::typemake;
+use std::fs::{read_to_string, write};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+#[test]
+fn timeout() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool slow:
+  output: 'done.txt'
+  timeout: 0.5
+  interpreter: 'sleep 30 & sleep 30; touch done.txt'
+",
+    )
+    .unwrap();
+
+    let start = Instant::now();
+    assert!(!typemake(directory.path(), &[]).status.success());
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(!directory.path().join("done.txt").exists());
+}
+
+#[test]
+fn retries_with_scaling() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool flaky:
+  output: 'memory.txt'
+  memory: 100
+  retries: 2
+  retry_backoff: 0.1
+  retry_scaling: 2
+  interpreter: 'test $TYPEMAKE_ATTEMPT -ge 3 && echo $TYPEMAKE_MEMORY > memory.txt'
+",
+    )
+    .unwrap();
+
+    assert!(typemake(directory.path(), &[]).status.success());
+    assert_eq!(
+        read_to_string(directory.path().join("memory.txt")).unwrap(),
+        "400\n"
+    );
+}
+
+#[test]
+fn retries_exhausted() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool flaky:
+  retries: 1
+  interpreter: 'echo attempt $TYPEMAKE_ATTEMPT && false'
+",
+    )
+    .unwrap();
+
+    assert!(!typemake(directory.path(), &[]).status.success());
+    let log = read_to_string(directory.path().join(".typemake/logs/flaky.log")).unwrap();
+    assert_eq!(log, "attempt 1\nattempt 2\n");
+}
+
+#[test]
+fn invalid_durations() {
+    for (property, value) in [
+        ("timeout", "-5"),
+        ("timeout", "float('nan')"),
+        ("retry_backoff", "float('inf')"),
+    ] {
+        let directory = tempdir().unwrap();
+        write(
+            directory.path().join("Typefile"),
+            format!(
+                "
+tool invalid:
+  {}: {}
+  interpreter: 'true'
+",
+                property, value
+            ),
+        )
+        .unwrap();
+
+        let output = typemake(directory.path(), &[]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("expected a finite non-negative float"));
+        assert!(!stderr.contains("panicked"));
+    }
+
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool flaky:
+  retries: 1
+  interpreter: 'false'
+",
+    )
+    .unwrap();
+    let output = typemake(directory.path(), &["--resources", "timeout=-1"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Expected a resource of the form"));
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::typemake;
+use std::fs::{create_dir_all, read_to_string, set_permissions, write, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use tempfile::tempdir;
+
+/// A stand-in for `Rscript`, recording the name and contents of the script it was given.
+const FAKE_RSCRIPT: &str = r#"#!/bin/bash
+basename "$1" > rscript.txt
+cat "$1" >> rscript.txt
+"#;
+
+const TYPEFILE: &str = r#"
+tool strict:
+  output: 'strict.txt'
+  interpreter: 'false; touch strict.txt'
+
+tool unset:
+  output: 'unset.txt'
+  shell: 'bash'
+  interpreter: 'echo $UNSET_VARIABLE > unset.txt'
+
+tool posix:
+  output: 'posix.txt'
+  shell: 'sh'
+  interpreter: 'false; touch posix.txt'
+
+tool python:
+  output: 'python.txt'
+  shell: 'python'
+  interpreter: "open('python.txt', 'w').write('from python\\n')"
+
+tool rscript:
+  output: 'rscript.txt'
+  shell: 'Rscript'
+  interpreter: "writeLines('from R', 'rscript.txt')"
+
+tool custom:
+  output: 'custom.txt'
+  shell: config.get('custom', 'cat {script} > custom.txt')
+  interpreter: 'from a custom shell'
+
+tool fishy:
+  output: 'fishy.txt'
+  shell: 'fish'
+  interpreter: 'touch fishy.txt'
+"#;
+
+#[test]
+fn shells() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+    create_dir_all(directory.join("bin")).unwrap();
+    write(directory.join("bin/Rscript"), FAKE_RSCRIPT).unwrap();
+    set_permissions(directory.join("bin/Rscript"), Permissions::from_mode(0o755)).unwrap();
+
+    let output = typemake(
+        directory,
+        &["posix.txt", "python.txt", "rscript.txt", "custom.txt"],
+    );
+    assert!(output.status.success());
+    assert!(directory.join("posix.txt").exists());
+    assert_eq!(
+        read_to_string(directory.join("python.txt")).unwrap(),
+        "from python\n"
+    );
+    assert_eq!(
+        read_to_string(directory.join("rscript.txt")).unwrap(),
+        ".typemake-script.R\nwriteLines('from R', 'rscript.txt')\n"
+    );
+    assert_eq!(
+        read_to_string(directory.join("custom.txt")).unwrap(),
+        "from a custom shell\n"
+    );
+
+    // Changing the shell causes a rerun.
+    let output = typemake(
+        directory,
+        &[
+            "--config",
+            "custom=cat {script} {script} > custom.txt",
+            "custom.txt",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Tool instance \"custom\" is outdated: its shell changed"));
+    assert_eq!(
+        read_to_string(directory.join("custom.txt")).unwrap(),
+        "from a custom shell\nfrom a custom shell\n"
+    );
+
+    // Bash runs in strict mode by default, and the script is kept in the staging directory if it fails.
+    let output = typemake(directory, &["strict.txt"]);
+    assert!(!output.status.success());
+    assert!(!directory.join("strict.txt").exists());
+    let script_path = directory.join(".typemake/staging/strict/.typemake-script.sh");
+    assert!(String::from_utf8(output.stderr).unwrap().contains(&format!(
+        "The script of tool instance \"strict\" is kept for inspection in {:?}",
+        script_path.strip_prefix(directory).unwrap()
+    )));
+    assert_eq!(
+        read_to_string(script_path).unwrap(),
+        "set -euo pipefail\nfalse; touch strict.txt\n"
+    );
+
+    // Explicitly selected bash runs in strict mode as well.
+    let output = typemake(directory, &["unset.txt"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("UNSET_VARIABLE: unbound variable"));
+
+    let output = typemake(directory, &["fishy.txt"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains(
+        "shell: unknown shell \"fish\", expected bash, sh, python, Rscript or a command containing {script}"
+    ));
+}
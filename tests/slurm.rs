@@ -0,0 +1,261 @@
+mod common;
+
+use common::typemake_command;
+use std::fs::{create_dir_all, read_dir, read_to_string, set_permissions, write, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Output;
+use tempfile::tempdir;
+
+/// Runs typemake with the slurm executor.
+fn typemake(directory: &Path, arguments: &[&str]) -> Output {
+    typemake_command(directory)
+        .args(["--executor", "slurm"])
+        .args(arguments)
+        .output()
+        .unwrap()
+}
+
+/// A stand-in for `sbatch`, running each job or array task in the background and recording its state in `states`.
+const FAKE_SBATCH: &str = r#"#!/bin/bash
+script="${@: -1}"
+if test -e reject-memory && grep -q -- '--mem=' "$script"; then
+  echo "sbatch: error: Memory specification can not be satisfied" >&2
+  exit 1
+fi
+mkdir -p submissions states
+id=$(( $(cat next-id 2>/dev/null || echo 100) + 1 ))
+echo $id > next-id
+echo "$@" > submissions/$id.args
+cp "$script" submissions/$id.sh
+last_task=$(sed -n 's/^#SBATCH --array=0-\([0-9]*\)$/\1/p' "$script")
+if test -n "$last_task"; then
+  for task in $(seq 0 $last_task); do
+    echo "${id}_$task|PENDING|0:0" > states/${id}_$task
+    SLURM_ARRAY_TASK_ID=$task slurm-task ${id}_$task "$script" > /dev/null 2>&1 &
+  done
+else
+  echo "$id|PENDING|0:0" > states/$id
+  slurm-task $id "$script" > /dev/null 2>&1 &
+fi
+echo "$id;fake-cluster"
+"#;
+
+/// Executes a job, like a compute node would.
+const FAKE_TASK: &str = r#"#!/bin/bash
+echo "$1|RUNNING|0:0" > states/$1
+bash "$2"
+code=$?
+if test $code = 0; then state=COMPLETED; else state=FAILED; fi
+echo "$1|$state|$code:0" > states/$1
+"#;
+
+/// A stand-in for `sacct`, printing the recorded states of the jobs given by `--jobs`.
+const FAKE_SACCT: &str = r#"#!/bin/bash
+if test -e no-accounting; then
+  echo "Slurm accounting storage is disabled" >&2
+  exit 1
+fi
+for argument in "$@"; do
+  case $argument in
+    --jobs=*) ids=${argument#--jobs=} ;;
+  esac
+done
+for id in ${ids//,/ }; do
+  cat states/$id states/${id}_* 2>/dev/null || true
+done
+"#;
+
+/// A stand-in for `squeue`, printing the jobs that have not terminated.
+const FAKE_SQUEUE: &str = r#"#!/bin/bash
+if test -e no-queue; then
+  echo "slurm_load_jobs error: Unable to contact slurm controller" >&2
+  exit 1
+fi
+cat states/* 2>/dev/null | grep -E '\|(PENDING|RUNNING)\|' | cut -d '|' -f 1,2
+"#;
+
+const TYPEFILE: &str = "
+tool alpha:
+  output: 'alpha.txt'
+  threads: 1
+  interpreter: 'echo task ${SLURM_ARRAY_TASK_ID:-}; touch alpha.txt'
+
+tool beta:
+  output: 'beta.txt'
+  threads: 1
+  interpreter: 'echo task ${SLURM_ARRAY_TASK_ID:-}; touch beta.txt'
+
+tool gamma:
+  output: 'gamma.txt'
+  threads: 1
+  interpreter: 'echo task ${SLURM_ARRAY_TASK_ID:-}; touch gamma.txt'
+
+tool large:
+  output: 'large.txt'
+  threads: 4
+  memory: 2000
+  timeout: 5400
+  interpreter: 'echo $TYPEMAKE_THREADS $TYPEMAKE_MEMORY > large.txt'
+
+tool failing:
+  output: 'failing.txt'
+  interpreter: 'echo failing; exit 3'
+";
+
+fn write_slurm(directory: &Path) {
+    write(directory.join("Typefile"), TYPEFILE).unwrap();
+    create_dir_all(directory.join("bin")).unwrap();
+    for (name, content) in [
+        ("sbatch", FAKE_SBATCH),
+        ("slurm-task", FAKE_TASK),
+        ("sacct", FAKE_SACCT),
+        ("squeue", FAKE_SQUEUE),
+    ] {
+        write(directory.join("bin").join(name), content).unwrap();
+        set_permissions(
+            directory.join("bin").join(name),
+            Permissions::from_mode(0o755),
+        )
+        .unwrap();
+    }
+}
+
+/// Returns the recorded submission scripts, ordered by job id.
+fn submissions(directory: &Path) -> Vec<String> {
+    let mut paths: Vec<_> = read_dir(directory.join("submissions"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "sh"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| read_to_string(path).unwrap())
+        .collect()
+}
+
+#[test]
+fn slurm_executor() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write_slurm(directory);
+
+    let output = typemake(
+        directory,
+        &[
+            "-j",
+            "4",
+            "--sbatch-arguments",
+            "--partition=short",
+            "alpha.txt",
+            "beta.txt",
+            "gamma.txt",
+            "large.txt",
+        ],
+    );
+    assert!(output.status.success());
+    for output in ["alpha.txt", "beta.txt", "gamma.txt"] {
+        assert!(directory.join(output).exists());
+    }
+    assert_eq!(
+        read_to_string(directory.join("large.txt")).unwrap(),
+        "4 2000\n"
+    );
+
+    // The small instances request the same resources, so they are submitted as one job array.
+    let submissions = submissions(directory);
+    assert_eq!(submissions.len(), 2);
+    let (array, large) = if submissions[0].contains("--array") {
+        (&submissions[0], &submissions[1])
+    } else {
+        (&submissions[1], &submissions[0])
+    };
+    assert!(array.contains("#SBATCH --array=0-2\n"));
+    assert!(array.contains("#SBATCH --cpus-per-task=1\n"));
+    assert!(large.contains("#SBATCH --job-name=large\n"));
+    assert!(large.contains("#SBATCH --cpus-per-task=4\n"));
+    assert!(large.contains("#SBATCH --mem=2000M\n"));
+    assert!(large.contains("#SBATCH --time=1:30:00\n"));
+    assert!(read_to_string(directory.join("submissions/101.args"))
+        .unwrap()
+        .starts_with("--parsable --partition=short "));
+    assert!(read_to_string(directory.join(".typemake/logs/beta.log"))
+        .unwrap()
+        .contains("task 1"));
+
+    let output = typemake(directory, &["failing.txt"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("ended in state FAILED"));
+    assert!(stderr.contains("Tool instance \"failing\" failed with exit status: 3"));
+}
+
+#[test]
+fn slurm_executor_without_accounting() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write_slurm(directory);
+    write(directory.join("no-accounting"), "").unwrap();
+
+    // Without sacct, termination is detected from the exit status written by the job script.
+    let output = typemake(directory, &["-j", "2", "alpha.txt", "beta.txt"]);
+    assert!(output.status.success());
+    assert!(directory.join("alpha.txt").exists());
+    assert!(directory.join("beta.txt").exists());
+
+    let output = typemake(directory, &["failing.txt"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Tool instance \"failing\" failed with exit status: 3"));
+}
+
+#[test]
+fn slurm_executor_without_queue() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write_slurm(directory);
+    write(directory.join("no-accounting"), "").unwrap();
+    write(directory.join("no-queue"), "").unwrap();
+    write(
+        directory.join("Typefile"),
+        "
+tool slow:
+  output: 'slow.txt'
+  interpreter: 'sleep 1; touch slow.txt'
+",
+    )
+    .unwrap();
+
+    // If the queue cannot be queried, running jobs are not considered vanished.
+    let output = typemake(directory, &["slow.txt"]);
+    assert!(output.status.success());
+    assert!(directory.join("slow.txt").exists());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Could not query job states with squeue"));
+    assert!(!stderr.contains("left the queue"));
+}
+
+#[test]
+fn slurm_submission_failure() {
+    let directory = tempdir().unwrap();
+    let directory = directory.path();
+    write_slurm(directory);
+    write(directory.join("reject-memory"), "").unwrap();
+
+    // Instances that cannot be submitted fail, without blocking the others.
+    let output = typemake(
+        directory,
+        &["-j", "2", "--keep-going", "alpha.txt", "large.txt"],
+    );
+    assert!(!output.status.success());
+    assert!(directory.join("alpha.txt").exists());
+    assert!(!directory.join("large.txt").exists());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Could not submit tool instance \"large\""));
+    assert!(stderr.contains("Failed tool instances: \"large\""));
+    assert!(read_to_string(directory.join(".typemake/logs/large.log"))
+        .unwrap()
+        .contains("sbatch: error: Memory specification can not be satisfied"));
+}
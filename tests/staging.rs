@@ -0,0 +1,47 @@
+mod common;
+
+use common::typemake;
+use std::fs::{create_dir, read_to_string, write};
+use tempfile::tempdir;
+
+#[test]
+fn failed_outputs_are_not_published() {
+    let directory = tempdir().unwrap();
+    create_dir(directory.path().join("results")).unwrap();
+    write(directory.path().join("results/existing.txt"), "existing\n").unwrap();
+    write(directory.path().join("input.txt"), "input\n").unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool copy:
+  input: 'input.txt'
+  output: ['results/copy.txt', 'results/nested/copy.txt']
+  interpreter: 'cat input.txt results/existing.txt > results/copy.txt && mkdir -p results/nested && cp results/copy.txt results/nested/copy.txt && test -e publish'
+",
+    )
+    .unwrap();
+
+    assert!(!typemake(directory.path(), &[]).status.success());
+    assert!(!directory.path().join("results/copy.txt").exists());
+    assert!(!directory.path().join("results/nested").exists());
+    assert_eq!(
+        read_to_string(
+            directory
+                .path()
+                .join(".typemake/staging/copy/results/copy.txt")
+        )
+        .unwrap(),
+        "input\nexisting\n"
+    );
+
+    write(directory.path().join("publish"), "").unwrap();
+    assert!(typemake(directory.path(), &[]).status.success());
+    for output in ["results/copy.txt", "results/nested/copy.txt"] {
+        assert_eq!(
+            read_to_string(directory.path().join(output)).unwrap(),
+            "input\nexisting\n"
+        );
+    }
+    assert!(directory.path().join("results/existing.txt").exists());
+    assert!(!directory.path().join(".typemake/staging/copy").exists());
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::typemake;
+use std::fs::{read_to_string, write};
+use tempfile::tempdir;
+
+#[test]
+fn stats() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool sleepy:
+  interpreter: 'sleep 0.2'
+
+tool quick:
+  interpreter: 'true'
+",
+    )
+    .unwrap();
+
+    assert!(typemake(directory.path(), &[]).status.success());
+
+    let metadata = read_to_string(directory.path().join(".typemake/metadata/sleepy.json")).unwrap();
+    assert!(metadata.contains("\"wall_time\""));
+    assert!(metadata.contains("\"max_rss\""));
+
+    let output = typemake(directory.path(), &["stats"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let slowest = stdout.split("Slowest executions:").nth(1).unwrap();
+    let sleepy_index = slowest.find("sleepy").unwrap();
+    let quick_index = slowest.find("quick").unwrap();
+    assert!(sleepy_index < quick_index);
+}
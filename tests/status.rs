@@ -0,0 +1,79 @@
+mod common;
+
+use common::typemake;
+use std::fs::write;
+use tempfile::tempdir;
+
+/// Returns the row of the given tool in the output of the status command, with whitespace normalised.
+fn status_row(stdout: &str, tool: &str) -> String {
+    stdout
+        .lines()
+        .find(|line| line.starts_with(&format!("{} ", tool)))
+        .unwrap_or_else(|| panic!("No status row for {:?} in {:?}", tool, stdout))
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn status() {
+    let directory = tempdir().unwrap();
+    write(directory.path().join("input.txt"), "input\n").unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool first:
+  input: 'input.txt'
+  output: 'first.txt'
+  interpreter: 'cp input.txt first.txt'
+
+tool second:
+  input: 'first.txt'
+  output: 'second.txt'
+  interpreter: 'cp first.txt second.txt'
+
+tool broken:
+  output: 'broken.txt'
+  interpreter: 'false'
+
+tool fresh:
+  output: 'fresh.txt'
+  interpreter: 'touch fresh.txt'
+
+tool dynamic:
+  output: open('missing.txt').read().split()
+  interpreter: 'true'
+",
+    )
+    .unwrap();
+
+    assert!(typemake(directory.path(), &["second.txt"]).status.success());
+    assert!(!typemake(directory.path(), &["broken.txt"]).status.success());
+
+    let output = typemake(directory.path(), &["status"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(status_row(&stdout, "first"), "first 1 0 0 0 0");
+    assert_eq!(status_row(&stdout, "second"), "second 1 0 0 0 0");
+    assert_eq!(status_row(&stdout, "broken"), "broken 0 0 0 1 0");
+    assert_eq!(status_row(&stdout, "fresh"), "fresh 0 0 1 0 0");
+    assert!(status_row(&stdout, "dynamic").starts_with("dynamic 0 0 1 0 0 not instantiated yet"));
+    assert!(stdout
+        .contains("2 of 5 tool instances done (40.0%), 0 stale, 2 pending, 1 failed, 0 running"));
+
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    write(directory.path().join("input.txt"), "changed\n").unwrap();
+    let output = typemake(directory.path(), &["status", "second.txt"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        status_row(&stdout, "first"),
+        "first 0 1 0 0 0 input \"input.txt\" is newer than its outputs"
+    );
+    assert_eq!(
+        status_row(&stdout, "second"),
+        "second 0 1 0 0 0 its predecessor \"first\" is outdated"
+    );
+    assert!(!stdout.lines().any(|line| line.starts_with("broken ")));
+    assert!(stdout.contains("0 of 3 tool instances done (0.0%), 2 stale, 1 pending"));
+}
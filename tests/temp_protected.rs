@@ -0,0 +1,176 @@
+mod common;
+
+use common::typemake;
+use std::fs::{read_to_string, write};
+use tempfile::tempdir;
+
+#[test]
+fn temp_outputs() {
+    let directory = tempdir().unwrap();
+    write(directory.path().join("extra.txt"), "extra\n").unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool intermediate:
+  output: temp('intermediate.txt')
+  interpreter: 'echo intermediate > intermediate.txt'
+
+tool result:
+  input: ['intermediate.txt', 'extra.txt']
+  output: 'result.txt'
+  interpreter: 'cat intermediate.txt extra.txt > result.txt'
+",
+    )
+    .unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(output.status.success());
+    assert!(!directory.path().join("intermediate.txt").exists());
+    assert_eq!(
+        read_to_string(directory.path().join("result.txt")).unwrap(),
+        "intermediate\nextra\n"
+    );
+
+    // The removed temporary output does not cause its consumers to be executed again.
+    let output = typemake(directory.path(), &[]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Tool instance \"intermediate\" is up to date"));
+    assert!(stderr.contains("Tool instance \"result\" is up to date"));
+
+    // If a consumer needs to be executed again, the temporary output is produced again.
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    write(directory.path().join("extra.txt"), "changed\n").unwrap();
+    let output = typemake(directory.path(), &[]);
+    assert!(output.status.success());
+    assert!(!directory.path().join("intermediate.txt").exists());
+    assert_eq!(
+        read_to_string(directory.path().join("result.txt")).unwrap(),
+        "intermediate\nchanged\n"
+    );
+
+    // Temporary outputs requested as targets are kept.
+    let output = typemake(directory.path(), &["intermediate.txt"]);
+    assert!(output.status.success());
+    assert!(directory.path().join("intermediate.txt").exists());
+}
+
+#[test]
+fn protected_outputs() {
+    let directory = tempdir().unwrap();
+    write(directory.path().join("input.txt"), "first\n").unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool expensive:
+  input: 'input.txt'
+  output: protected('result.txt')
+  interpreter: 'cp input.txt result.txt'
+",
+    )
+    .unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(output.status.success());
+    let result = directory.path().join("result.txt");
+    assert!(result.metadata().unwrap().permissions().readonly());
+
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    write(directory.path().join("input.txt"), "second\n").unwrap();
+    let output = typemake(directory.path(), &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("would overwrite the protected outputs [\"result.txt\"]"));
+    assert_eq!(read_to_string(&result).unwrap(), "first\n");
+
+    let output = typemake(directory.path(), &["--force"]);
+    assert!(output.status.success());
+    assert_eq!(read_to_string(&result).unwrap(), "second\n");
+    assert!(result.metadata().unwrap().permissions().readonly());
+}
+
+#[test]
+fn shadowed_flag_functions() {
+    let directory = tempdir().unwrap();
+    // Typefiles may use the names of the flag functions for their own definitions.
+    write(
+        directory.path().join("Typefile"),
+        "
+temp = 'intermediate.txt'
+
+tool intermediate:
+  output: 'intermediate.txt'
+  interpreter: 'echo intermediate > ' + temp
+",
+    )
+    .unwrap();
+    let output = typemake(directory.path(), &[]);
+    assert!(output.status.success());
+    assert!(directory.path().join("intermediate.txt").exists());
+
+    // But using such a definition to declare artifacts is an error, since it is most likely a mistake.
+    write(
+        directory.path().join("Typefile"),
+        "
+temp = '/tmp'
+
+tool intermediate:
+  output: temp('intermediate.txt')
+  interpreter: 'echo intermediate > intermediate.txt'
+",
+    )
+    .unwrap();
+    let output = typemake(directory.path(), &["--force"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains(
+        "the typefile defines \"temp\", which shadows the typemake function of the same name"
+    ));
+
+    // The flag functions are not part of the namespace of the typefile, but remain available in it.
+    write(
+        directory.path().join("Typefile"),
+        "
+assert 'temp' not in globals()
+
+tool intermediate:
+  output: temp('intermediate.txt')
+  interpreter: 'echo intermediate > intermediate.txt'
+",
+    )
+    .unwrap();
+    let output = typemake(directory.path(), &["intermediate.txt"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn temp_checkpoint_output() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+def cluster_files():
+    with open('clusters.txt') as clusters:
+        return ['cluster_{}.txt'.format(i) for i in range(int(clusters.read()))]
+
+tool clusters:
+  checkpoint
+  output: temp('clusters.txt')
+  interpreter: 'echo 3 > clusters.txt'
+
+tool split:
+  input: 'clusters.txt'
+  output: cluster_files()
+  interpreter: ' && '.join('touch ' + file for file in cluster_files())
+",
+    )
+    .unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(output.status.success());
+    for i in 0..3 {
+        assert!(directory.path().join(format!("cluster_{}.txt", i)).exists());
+    }
+    // The checkpoint output is removed once the tools instantiated after the checkpoint consumed it.
+    assert!(!directory.path().join("clusters.txt").exists());
+}
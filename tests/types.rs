@@ -0,0 +1,94 @@
+mod common;
+
+use common::typemake;
+use std::fs::write;
+use tempfile::tempdir;
+
+const TYPES: &str = "
+type csv_table:
+  validator: lambda path: ',' in open(path).readline()
+
+type text:
+";
+
+#[test]
+fn type_mismatch() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        format!(
+            "{}
+tool produce:
+  output: typed('text', 'table.csv')
+  interpreter: 'echo a,b > table.csv'
+
+tool consume:
+  input: typed('csv_table', 'table.csv')
+  output: 'result.txt'
+  interpreter: 'cp table.csv result.txt'
+",
+            TYPES
+        ),
+    )
+    .unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains(
+        "Tool instance \"produce\" produces artifact \"table.csv\" of type \"text\", but tool instance \"consume\" requires it to be of type \"csv_table\""
+    ));
+    assert!(!directory.path().join("table.csv").exists());
+}
+
+#[test]
+fn unknown_type() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool produce:
+  output: typed('tsv_table', 'table.tsv')
+  interpreter: 'touch table.tsv'
+",
+    )
+    .unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown type \"tsv_table\""));
+}
+
+#[test]
+fn validator() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        format!(
+            "{}
+tool produce:
+  output: typed('csv_table', 'table.csv')
+  interpreter: 'if test -e valid; then echo a,b; else echo a b; fi > table.csv'
+
+tool consume:
+  input: typed('csv_table', 'table.csv')
+  output: nonempty(typed('text', 'result.txt'))
+  interpreter: 'cp table.csv result.txt'
+",
+            TYPES
+        ),
+    )
+    .unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("\"table.csv\" is not a valid csv_table"));
+    assert!(!directory.path().join("table.csv").exists());
+
+    write(directory.path().join("valid"), "").unwrap();
+    assert!(typemake(directory.path(), &[]).status.success());
+    assert!(directory.path().join("result.txt").exists());
+}
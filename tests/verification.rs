@@ -0,0 +1,93 @@
+mod common;
+
+use common::typemake;
+use std::fs::{read_to_string, write};
+use tempfile::tempdir;
+
+#[test]
+fn missing_outputs() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool partial:
+  output: ['a.txt', 'b.txt', nonempty('c.txt')]
+  interpreter: 'echo a > a.txt && touch c.txt'
+",
+    )
+    .unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("did not produce the declared outputs [\"b.txt\", \"c.txt\"]"));
+    assert!(!directory.path().join("a.txt").exists());
+    let metadata =
+        read_to_string(directory.path().join(".typemake/metadata/partial.json")).unwrap();
+    assert!(metadata.contains("\"success\": false"));
+}
+
+#[test]
+fn latency_wait() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool late:
+  output: nonempty('late.txt')
+  interpreter: '(sleep 0.3; echo late > late.txt) > /dev/null 2>&1 &'
+",
+    )
+    .unwrap();
+
+    assert!(typemake(directory.path(), &["--latency-wait", "5"])
+        .status
+        .success());
+    assert_eq!(
+        read_to_string(directory.path().join("late.txt")).unwrap(),
+        "late\n"
+    );
+}
+
+#[test]
+fn missing_outputs_without_script() {
+    let directory = tempdir().unwrap();
+    write(
+        directory.path().join("Typefile"),
+        "
+tool declared:
+  output: ['present.txt', 'absent.txt']
+",
+    )
+    .unwrap();
+    write(directory.path().join("present.txt"), "present\n").unwrap();
+
+    let output = typemake(directory.path(), &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains(
+        "Tool instance \"declared\" did not produce the declared outputs [\"absent.txt\"]"
+    ));
+    assert!(!directory
+        .path()
+        .join(".typemake/metadata/declared.json")
+        .exists());
+
+    write(directory.path().join("absent.txt"), "present as well\n").unwrap();
+    assert!(typemake(directory.path(), &[]).status.success());
+}
+
+#[test]
+fn invalid_latency_wait() {
+    let directory = tempdir().unwrap();
+    write(directory.path().join("Typefile"), "").unwrap();
+
+    for latency_wait in ["inf", "NaN", "1e30", "-1"] {
+        let argument = format!("--latency-wait={}", latency_wait);
+        let output = typemake(directory.path(), &[&argument]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Expected a finite non-negative number of seconds"));
+        assert!(!stderr.contains("panicked"));
+    }
+}